@@ -0,0 +1,62 @@
+// ages are given on the command line as a number followed by a unit (eg. 30d or 6mo)
+use ::std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+const UNITS: &[(&[&str], u64)] = &[
+    (&["s", "sec", "secs", "second", "seconds"], 1),
+    (&["m", "min", "mins", "minute", "minutes"], MINUTE),
+    (&["h", "hour", "hours"], HOUR),
+    (&["d", "day", "days"], DAY),
+    (&["w", "week", "weeks"], 7 * DAY),
+    (&["mo", "month", "months"], 30 * DAY),
+    (&["y", "year", "years"], 365 * DAY),
+];
+
+pub fn parse_age(age: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "invalid age '{}', expected a number followed by s, m, h, d, w, mo or y (eg. 30d or 6mo)",
+            age
+        )
+    };
+    let unit_start = age
+        .find(|character: char| !character.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (count, unit) = age.split_at(unit_start);
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    let (_, unit_seconds) = UNITS
+        .iter()
+        .find(|(names, _)| names.contains(&unit))
+        .ok_or_else(invalid)?;
+    let seconds = count.checked_mul(*unit_seconds).ok_or_else(invalid)?;
+    Ok(Duration::from_secs(seconds))
+}
+
+pub fn age_cutoff(age: Duration) -> u64 {
+    // the time (in seconds since the unix epoch) that was this long ago
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_secs().saturating_sub(age.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ages() {
+        assert_eq!(parse_age("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_age("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_age("2days"), Ok(Duration::from_secs(2 * DAY)));
+        assert_eq!(parse_age("6mo"), Ok(Duration::from_secs(180 * DAY)));
+        assert_eq!(parse_age("1y"), Ok(Duration::from_secs(365 * DAY)));
+        assert!(parse_age("6").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("6 months").is_err());
+        assert!(parse_age("6fortnights").is_err());
+    }
+}
@@ -1,15 +1,32 @@
+use ::std::collections::HashSet;
+use ::std::ffi::OsString;
 use ::std::fs::{self, Metadata};
-use ::std::mem::ManuallyDrop;
-use ::std::path::PathBuf;
+use ::std::mem::{self, ManuallyDrop};
+use ::std::path::{Component, Path, PathBuf};
+use ::std::slice;
+use ::std::sync::atomic::{AtomicBool, Ordering};
 use ::std::sync::mpsc::{Receiver, SyncSender};
+use ::std::sync::Arc;
+use ::std::thread::park_timeout;
+use ::std::{thread, time};
 use ::tui::backend::Backend;
 
+use crate::age::age_cutoff;
+use crate::audit_log::{AuditLog, Outcome};
+use crate::deleter::{remove_paths, DeletionProgress, Removal, Subtree};
+use crate::dry_run::DryRunScript;
 use crate::messages::{handle_instructions, Instruction};
-use crate::state::files::{FileOrFolder, FileTree, Folder};
-use crate::state::tiles::Board;
+use crate::protected_paths::ProtectedPaths;
+use crate::scanner::{scan_into, scan_state, walk_subfolder, ScanFilter, ScanState};
+use crate::state::files::{FileOrFolder, FileTree};
+use crate::state::path_prompt::PathPrompt;
+use crate::state::search::Search;
+use crate::state::tiles::{find_owner, Board, FileType, OwnerKind, View};
 use crate::state::{FileToDelete, UiEffects};
+use crate::trash::{move_to_trash, TrashedFile};
 use crate::ui::Display;
-use crate::Event;
+use crate::watcher::Watcher;
+use crate::{Event, StartOptions, SEARCH_DEBOUNCE, SHOULD_SHOW_LOADING_ANIMATION};
 
 #[derive(Clone)]
 pub enum UiMode {
@@ -17,24 +34,93 @@ pub enum UiMode {
     Normal,
     ScreenTooSmall,
     DeleteFile(FileToDelete),
+    DeleteMarkedFiles(Vec<FileToDelete>),
     ErrorMessage(String),
+    Search(Search),
+    PathPrompt(PathPrompt),
+    Help,
     Exiting { app_loaded: bool },
     WarningMessage(FileToDelete),
 }
 
+// a deletion the user asked for, some of which might still be running in the background
+struct Deletion {
+    // the mode showing its progress, which stays until it's done
+    ui_mode: UiMode,
+    // how many files and folders we were asked to delete
+    total: usize,
+    deleted: usize,
+    // the full path of every file we failed to delete, and why
+    errors: Vec<(String, String)>,
+    // the files being deleted by remove_paths, in the order it deletes them
+    in_progress: Vec<FileToDelete>,
+    cancelled: Arc<AtomicBool>,
+    // if we are purging the trash as we exit, what we put there for each file being deleted
+    purged: Vec<TrashedFile>,
+}
+
+impl Deletion {
+    fn new(ui_mode: UiMode, total: usize) -> Self {
+        Deletion {
+            ui_mode,
+            total,
+            deleted: 0,
+            errors: vec![],
+            in_progress: vec![],
+            cancelled: Arc::new(AtomicBool::new(false)),
+            purged: vec![],
+        }
+    }
+    fn add_error(&mut self, file_to_delete: &FileToDelete, msg: String) {
+        let full_path = file_to_delete.full_path().to_string_lossy().into_owned();
+        self.errors.push((full_path, msg));
+    }
+}
+
+// a deleted file or folder, which can be restored until we exit
+struct DeletedFile {
+    file_to_delete: FileToDelete,
+    trashed_file: TrashedFile,
+    // what we know of it from the scan if it was deleted permanently, in
+    // which case it is only in the trash until we exit
+    staged: Option<Subtree>,
+}
+
 pub struct App<B>
 where
     B: Backend,
 {
     pub is_running: bool,
     pub loaded: bool,
+    // nothing can be deleted (see --read-only)
+    pub read_only: bool,
     pub ui_mode: UiMode,
     board: Board,
     file_tree: ManuallyDrop<FileTree>,
     display: Display<B>,
     event_sender: SyncSender<Event>,
+    instruction_sender: SyncSender<Instruction>,
     ui_effects: UiEffects,
     delete_confirmation_disabled: bool,
+    move_to_trash: bool,
+    // the files we deleted, last one last
+    deleted_files: Vec<DeletedFile>,
+    dry_run_script: Option<DryRunScript>,
+    audit_log: Option<AuditLog>,
+    // shown once we're done deleting
+    audit_log_error: Option<String>,
+    // files being deleted in the background
+    deletion: Option<Deletion>,
+    protected_paths: ProtectedPaths,
+    // files and folders marked to be deleted together, in the order they were marked
+    marked_files: Vec<FileToDelete>,
+    save_snapshot: Option<PathBuf>,
+    open_path: Option<PathBuf>,
+    scan_filter: ScanFilter,
+    // watches the folders we find when rescanning, if we are watching for changes
+    watcher: Option<Watcher>,
+    // changes were lost while we were rescanning a folder
+    rescan_base_folder_next: bool,
 }
 
 impl<B> App<B>
@@ -43,31 +129,49 @@ where
 {
     pub fn new(
         terminal_backend: B,
-        path_in_filesystem: PathBuf,
+        file_tree: FileTree,
         event_sender: SyncSender<Event>,
-        show_apparent_size: bool,
-        disable_delete_confirmation: bool,
+        instruction_sender: SyncSender<Instruction>,
+        watcher: Option<Watcher>,
+        options: StartOptions,
     ) -> Self {
         let display = Display::new(terminal_backend);
-        let board = Board::new(&Folder::new(&path_in_filesystem));
-        let base_folder = Folder::new(&path_in_filesystem);
-        let file_tree = ManuallyDrop::new(FileTree::new(
-            base_folder,
-            path_in_filesystem,
-            show_apparent_size,
-        ));
+        let mut board = Board::new(file_tree.get_current_folder());
+        board.change_stale_before(options.stale_after.map(age_cutoff));
+        // a file tree loaded from a snapshot is already complete
+        let mut protected_paths = options.protected_paths;
+        protected_paths
+            .add_scanned_folder(&file_tree.path_in_filesystem, file_tree.get_base_folder());
+        let file_tree = ManuallyDrop::new(file_tree);
         // we use ManuallyDrop here because otherwise the app takes forever to exit
-        let ui_effects = UiEffects::new();
+        let mut ui_effects = UiEffects::new();
+        ui_effects.dry_run = options.dry_run_script.is_some();
+        ui_effects.read_only = options.read_only;
         App {
             is_running: true,
             loaded: false,
+            read_only: options.read_only,
             board,
             file_tree,
             display,
             ui_mode: UiMode::Loading,
             event_sender,
+            instruction_sender,
             ui_effects,
-            delete_confirmation_disabled: disable_delete_confirmation,
+            delete_confirmation_disabled: options.disable_delete_confirmation,
+            move_to_trash: options.move_to_trash,
+            deleted_files: vec![],
+            dry_run_script: options.dry_run_script,
+            audit_log: options.audit_log,
+            audit_log_error: None,
+            deletion: None,
+            protected_paths,
+            marked_files: vec![],
+            save_snapshot: options.save_snapshot,
+            open_path: options.open_path,
+            scan_filter: options.scan_filter,
+            watcher,
+            rescan_base_folder_next: false,
         }
     }
     pub fn start(&mut self, receiver: Receiver<Instruction>) {
@@ -75,8 +179,39 @@ where
         self.display.clear();
     }
     pub fn render_and_update_board(&mut self) {
+        let current_folder_names = &self.file_tree.current_folder_names;
+        let view = &self.board.view;
+        let marked_names = self
+            .marked_files
+            .iter()
+            .filter_map(|marked_file| {
+                let path = marked_file
+                    .path_to_file
+                    .strip_prefix(&current_folder_names[..])?;
+                match (view, path) {
+                    (View::Folder, [name]) => Some(name.clone()),
+                    // files anywhere in the folder are named by their path in it
+                    (View::Category(_), path) | (View::LargestFiles, path) if !path.is_empty() => {
+                        Some(path.iter().collect::<PathBuf>().into_os_string())
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        self.board.change_marked_names(marked_names);
         let current_folder = self.file_tree.get_current_folder();
-        self.board.change_files(&current_folder);
+        self.board.change_files(
+            &current_folder,
+            &self.file_tree.current_folder_names,
+            self.file_tree.changes(),
+        );
+        let protected_names = match self.board.view {
+            View::Categories | View::Owners(_) => HashSet::new(),
+            _ => self
+                .protected_paths
+                .protected_names(&self.file_tree.get_current_path(), self.board.file_names()),
+        };
+        self.board.change_protected_names(protected_names);
         self.render();
     }
     pub fn increment_loading_progress_indicator(&mut self) {
@@ -87,6 +222,19 @@ where
         if full_screen_size.width < 50 || full_screen_size.height < 15 {
             self.ui_mode = UiMode::ScreenTooSmall;
         }
+        self.ui_effects.undo_available = !self.deleted_files.is_empty();
+        let marked_files = self.marked_files_to_delete();
+        self.ui_effects.marked = if marked_files.is_empty() {
+            None
+        } else {
+            Some((
+                marked_files.len(),
+                marked_files
+                    .iter()
+                    .map(|marked_file| marked_file.size)
+                    .sum(),
+            ))
+        };
         self.display.render(
             &mut self.file_tree,
             &mut self.board,
@@ -109,13 +257,185 @@ where
     pub fn start_ui(&mut self) {
         self.ui_mode = UiMode::Normal;
         self.loaded = true;
+        if let Some(snapshot_path) = self.save_snapshot.take() {
+            if let Err(msg) = self.file_tree.save_snapshot(&snapshot_path) {
+                self.ui_mode = UiMode::ErrorMessage(format!("Failed to save snapshot: {}", msg));
+            }
+        }
+        if let Some(open_path) = self.open_path.take() {
+            if let Err(msg) = self.open_folder(&open_path) {
+                self.ui_mode = UiMode::ErrorMessage(msg);
+            }
+        }
         self.render_and_update_board();
     }
+    fn open_folder(&mut self, relative_path: &Path) -> Result<(), String> {
+        let error = || {
+            format!(
+                "Cannot open '{}': no such folder was scanned",
+                relative_path.to_string_lossy()
+            )
+        };
+        let mut folder_names = vec![];
+        for component in relative_path.components() {
+            match component {
+                Component::Normal(name) => folder_names.push(name.to_os_string()),
+                Component::CurDir => {}
+                _ => return Err(error()),
+            }
+        }
+        let mut folder = self.file_tree.get_base_folder();
+        for name in &folder_names {
+            folder = match folder.contents.get(name) {
+                Some(FileOrFolder::Folder(subfolder)) => subfolder,
+                _ => return Err(error()),
+            };
+        }
+        // entered one by one, as if the user did it, so that <ESC> leads back up
+        for name in &folder_names {
+            let index = self
+                .board
+                .tile_index(self.file_tree.get_current_folder(), name);
+            self.board.previous_indices_and_zoom_level.push((index, 0));
+            self.file_tree.enter_folder(name);
+        }
+        Ok(())
+    }
     pub fn add_entry_to_base_folder(&mut self, file_metadata: &Metadata, entry_path: PathBuf) {
         self.file_tree.add_entry(file_metadata, &entry_path);
+        self.protected_paths.add_scanned(&entry_path);
         self.ui_effects.last_read_path = Some(entry_path);
     }
+    pub fn add_mount_point_to_base_folder(&mut self, entry_path: PathBuf) {
+        self.file_tree.add_mount_point(&entry_path);
+        self.protected_paths.add_scanned(&entry_path);
+        self.ui_effects.last_read_path = Some(entry_path);
+    }
+    pub fn update_entry_in_base_folder(&mut self, file_metadata: &Metadata, entry_path: PathBuf) {
+        // the watcher also finds new files this way
+        self.file_tree.update_entry(file_metadata, &entry_path);
+        self.protected_paths.add_scanned(&entry_path);
+    }
+    pub fn remove_entry_from_base_folder(&mut self, entry_path: PathBuf) {
+        self.protected_paths.remove_scanned(&entry_path);
+        let left_folders = self.file_tree.remove_entry(&entry_path);
+        self.leave_removed_folders(left_folders);
+    }
+    pub fn toggle_apparent_size(&mut self) {
+        // the layout follows the apparent size or the disk size, both of
+        // which we already have
+        if self.file_tree.is_diff() {
+            // a comparison between two scans only has the sizes it was made with
+            return;
+        }
+        let show_apparent_size = !self.file_tree.show_apparent_size;
+        self.file_tree.set_show_apparent_size(show_apparent_size);
+        self.board.reset_zoom_index();
+        self.board.reset_selected_index();
+        self.render_and_update_board();
+    }
+    pub fn rescan_current_folder(&mut self) {
+        if self.file_tree.is_diff() {
+            self.ui_mode =
+                UiMode::ErrorMessage(String::from("Cannot rescan while comparing scans"));
+            self.render();
+            return;
+        }
+        if self.ui_effects.rescanning_folder {
+            return;
+        }
+        let path = self.file_tree.get_current_path();
+        if !path.is_dir() {
+            self.ui_mode = UiMode::ErrorMessage(format!(
+                "Folder '{}' no longer exists",
+                path.to_string_lossy()
+            ));
+            self.render();
+            return;
+        }
+        self.rescan_folder(path);
+    }
+    pub fn rescan_base_folder(&mut self) {
+        // some changes were lost (the watcher could not keep up), so we read everything again
+        if self.ui_effects.rescanning_folder {
+            self.rescan_base_folder_next = true;
+            return;
+        }
+        let path = self.file_tree.path_in_filesystem.clone();
+        self.rescan_folder(path);
+    }
+    fn rescan_folder(&mut self, path: PathBuf) {
+        self.ui_effects.rescanning_folder = true;
+        self.render();
+        let root = self.file_tree.path_in_filesystem.clone();
+        let file_tree = self.file_tree.subfolder_to_rescan(path);
+        let scan_filter = self.scan_filter.clone();
+        let watcher = self.watcher.clone();
+        let instruction_sender = self.instruction_sender.clone();
+        // this thread is not joined when the app exits, if it is still
+        // scanning then it is stopped along with the rest of the process
+        thread::Builder::new()
+            .name("folder_rescanner".to_string())
+            .spawn(move || {
+                let rescanned = Arc::new(AtomicBool::new(false));
+                let loading_loop = if SHOULD_SHOW_LOADING_ANIMATION {
+                    let instruction_sender = instruction_sender.clone();
+                    let rescanned = rescanned.clone();
+                    Some(thread::spawn(move || {
+                        while !rescanned.load(Ordering::Acquire) {
+                            let _ =
+                                instruction_sender.send(Instruction::ToggleScanningVisualIndicator);
+                            if instruction_sender.send(Instruction::Render).is_err() {
+                                break;
+                            }
+                            park_timeout(time::Duration::from_millis(100));
+                        }
+                    }))
+                } else {
+                    None
+                };
+                let file_tree = scan_into(file_tree, &root, &scan_filter, watcher.as_ref());
+                rescanned.store(true, Ordering::Release);
+                if let Some(loading_loop) = loading_loop {
+                    let _ = loading_loop.join();
+                }
+                let _ = instruction_sender
+                    .send(Instruction::ReplaceFolderInBaseFolder(Box::new(file_tree)));
+            })
+            .expect("failed to start rescanning folder");
+    }
+    pub fn replace_folder_in_base_folder(&mut self, file_tree: FileTree) {
+        self.ui_effects.rescanning_folder = false;
+        let path = &file_tree.path_in_filesystem;
+        self.protected_paths.remove_scanned(path);
+        self.protected_paths.add_scanned(path);
+        self.protected_paths
+            .add_scanned_folder(path, file_tree.get_base_folder());
+        let left_folders = self.file_tree.replace_folder(file_tree);
+        self.leave_removed_folders(left_folders);
+        self.render_and_update_board();
+        if self.rescan_base_folder_next {
+            self.rescan_base_folder_next = false;
+            self.rescan_base_folder();
+        }
+    }
+    fn leave_removed_folders(&mut self, left_folders: usize) {
+        for _ in 0..left_folders {
+            // the folder we were in was removed, so we go up to the closest one that's left
+            if let Some((index, zoom_level)) = self.board.pop_previous_index_and_zoom_level() {
+                if let Some(index) = index {
+                    self.board.set_selected_index(&index);
+                }
+                self.board.set_zoom_index(zoom_level);
+            }
+        }
+    }
     pub fn reset_ui_mode(&mut self) {
+        if let Some(deletion) = self.deletion.as_ref() {
+            // the progress is shown (and the deletion can be cancelled) until it's done
+            self.ui_mode = deletion.ui_mode.clone();
+            return;
+        }
         match self.ui_mode {
             UiMode::Loading | UiMode::Normal => {}
             _ => {
@@ -142,6 +462,10 @@ where
         self.render();
     }
     pub fn exit(&mut self) {
+        if !self.is_deleting() && self.purge_deleted_files() {
+            // we exit once they're gone
+            return;
+        }
         self.is_running = false;
         // here we do a blocking send rather than a try_send
         // because we want to make sure that if the receiver
@@ -172,6 +496,12 @@ where
         self.render();
     }
     pub fn enter_selected(&mut self) {
+        match &self.board.view {
+            View::Folder => {}
+            View::Categories => return self.enter_selected_category(),
+            View::Category(_) | View::LargestFiles => return self.jump_to_selected_file(),
+            View::Owners(kind) => return self.filter_by_selected_owner(*kind),
+        }
         self.board.record_current_index_and_zoom_level();
         if let Some(tile) = &self.board.currently_selected() {
             let selected_name = &tile.name;
@@ -179,6 +509,7 @@ where
                 match file_or_folder {
                     FileOrFolder::Folder(_) => {
                         self.file_tree.enter_folder(&selected_name);
+                        self.clear_search_filter();
                         self.board.reset_zoom_index();
                         self.board.reset_selected_index();
                         self.render_and_update_board();
@@ -189,33 +520,396 @@ where
         }
     }
     pub fn go_up(&mut self) {
+        if self.board.is_filtered() {
+            // the search filter is cleared before we leave the folder
+            self.clear_search_filter();
+            self.board.reset_zoom_index();
+            self.board.reset_selected_index();
+            self.render_and_update_board();
+            return;
+        }
+        match &self.board.view {
+            View::Folder => {}
+            View::Categories | View::LargestFiles | View::Owners(_) => {
+                return self.leave_view(View::Folder)
+            }
+            View::Category(_) => return self.leave_view(View::Categories),
+        }
         let succeeded = self.file_tree.leave_folder();
+        self.restore_previous_index_and_zoom_level();
+        self.render_and_update_board();
+        if !succeeded {
+            let _ = self.event_sender.try_send(Event::PathError);
+        }
+    }
+    fn restore_previous_index_and_zoom_level(&mut self) {
         if let Some((index, zoom_level)) = self.board.pop_previous_index_and_zoom_level() {
             if let Some(index) = index {
                 self.board.set_selected_index(&index);
             }
             self.board.set_zoom_index(zoom_level);
         }
+    }
+    pub fn toggle_categories(&mut self) {
+        match &self.board.view {
+            View::Folder => {}
+            View::Categories | View::Category(_) => return self.leave_views(),
+            View::LargestFiles | View::Owners(_) => self.restore_folder_view(),
+        }
+        self.enter_view(View::Categories);
+    }
+    pub fn toggle_largest_files(&mut self) {
+        match &self.board.view {
+            View::Folder => {}
+            View::LargestFiles => return self.leave_views(),
+            View::Categories | View::Category(_) | View::Owners(_) => self.restore_folder_view(),
+        }
+        self.enter_view(View::LargestFiles);
+    }
+    pub fn toggle_owners(&mut self) {
+        // users, then groups, then back to where we were
+        if self.board.owner_filter().is_some() {
+            self.board.change_owner_filter(None);
+            self.board.reset_zoom_index();
+            self.board.reset_selected_index();
+            self.render_and_update_board();
+            return;
+        }
+        match &self.board.view {
+            View::Folder => {}
+            View::Owners(OwnerKind::User) => {
+                self.board.change_view(View::Owners(OwnerKind::Group));
+                self.board.reset_zoom_index();
+                self.board.reset_selected_index();
+                self.render_and_update_board();
+                return;
+            }
+            View::Owners(OwnerKind::Group) => return self.leave_views(),
+            View::Categories | View::Category(_) | View::LargestFiles => self.restore_folder_view(),
+        }
+        self.enter_view(View::Owners(OwnerKind::User));
+    }
+    fn filter_by_selected_owner(&mut self, kind: OwnerKind) {
+        // only the files of this owner are shown until <o> is pressed again
+        let name = match self.board.currently_selected() {
+            Some(tile) => tile.name.clone(),
+            None => return,
+        };
+        let owner_filter = find_owner(self.file_tree.get_current_folder(), kind, &name);
+        self.board.change_owner_filter(owner_filter);
+        self.leave_view(View::Folder);
+    }
+    fn enter_selected_category(&mut self) {
+        if let Some(tile) = self.board.currently_selected() {
+            let category = tile.name.clone();
+            self.enter_view(View::Category(category));
+        }
+    }
+    fn jump_to_selected_file(&mut self) {
+        // the file is shown in the folder it's in
+        if let Some(path_to_file) = self.selected_path() {
+            self.jump_to(&path_to_file);
+        }
+    }
+    fn enter_view(&mut self, view: View) {
+        self.board.record_current_index_and_zoom_level();
+        self.clear_search_filter();
+        self.board.change_view(view);
+        self.board.reset_zoom_index();
+        self.board.reset_selected_index();
         self.render_and_update_board();
-        if !succeeded {
-            let _ = self.event_sender.try_send(Event::PathError);
+    }
+    fn leave_view(&mut self, view: View) {
+        self.clear_search_filter();
+        self.board.change_view(view);
+        self.board.reset_selected_index();
+        self.restore_previous_index_and_zoom_level();
+        self.render_and_update_board();
+    }
+    fn leave_views(&mut self) {
+        self.restore_folder_view();
+        self.render_and_update_board();
+    }
+    fn restore_folder_view(&mut self) {
+        // straight back to the folder, past the categories
+        if let View::Category(_) = &self.board.view {
+            self.board.pop_previous_index_and_zoom_level();
         }
+        self.clear_search_filter();
+        self.board.change_view(View::Folder);
+        self.board.reset_selected_index();
+        self.restore_previous_index_and_zoom_level();
     }
-    pub fn get_file_to_delete(&self) -> Option<FileToDelete> {
+    fn enter_path(&mut self, folder_names: &[OsString]) -> bool {
+        // false => there is no such folder
+        if !self.file_tree.set_current_folder(folder_names) {
+            return false;
+        }
+        // the same history we would have had entering these folders one by
+        // one, so that <ESC> leads back up through them
+        let mut previous_indices_and_zoom_level = vec![];
+        let mut folder = self.file_tree.get_base_folder();
+        for name in folder_names {
+            previous_indices_and_zoom_level.push((self.board.tile_index(folder, name), 0));
+            if let Some(FileOrFolder::Folder(subfolder)) = folder.contents.get(name) {
+                folder = subfolder;
+            }
+        }
+        self.board.previous_indices_and_zoom_level = previous_indices_and_zoom_level;
+        self.board.change_view(View::Folder);
+        self.clear_search_filter();
+        self.board.reset_zoom_index();
+        self.board.reset_selected_index();
+        true
+    }
+    pub fn jump_to(&mut self, path_to_file: &[OsString]) -> bool {
+        // goes to the folder the file is in and selects it,
+        // false => there is no such file or folder
+        let (name, folder_names) = match path_to_file.split_last() {
+            Some(name_and_folder_names) => name_and_folder_names,
+            None => return false,
+        };
+        if !self.enter_path(folder_names) {
+            return false;
+        }
+        let current_folder = self.file_tree.get_current_folder();
+        self.board.change_files(
+            current_folder,
+            &self.file_tree.current_folder_names,
+            self.file_tree.changes(),
+        );
+        let found = self.board.select_name(name, current_folder);
+        if !found {
+            self.board.reset_zoom_index();
+        }
+        self.render_and_update_board();
+        found
+    }
+    pub fn start_path_prompt(&mut self) {
+        self.ui_mode = UiMode::PathPrompt(PathPrompt::new());
+        self.render();
+    }
+    pub fn add_to_path_prompt(&mut self, character: char) {
+        if let UiMode::PathPrompt(path_prompt) = &mut self.ui_mode {
+            path_prompt.push(character);
+            self.render();
+        }
+    }
+    pub fn remove_from_path_prompt(&mut self) {
+        if let UiMode::PathPrompt(path_prompt) = &mut self.ui_mode {
+            path_prompt.pop();
+            self.render();
+        }
+    }
+    pub fn complete_path_prompt(&mut self) {
+        if let UiMode::PathPrompt(path_prompt) = &mut self.ui_mode {
+            path_prompt.complete(
+                self.file_tree.get_base_folder(),
+                &self.file_tree.path_in_filesystem,
+            );
+            self.render();
+        }
+    }
+    pub fn go_to_path(&mut self) {
+        let folder_names = match &self.ui_mode {
+            UiMode::PathPrompt(path_prompt) => {
+                path_prompt.folder_names(&self.file_tree.path_in_filesystem)
+            }
+            _ => return,
+        };
+        let mut full_path = self.file_tree.path_in_filesystem.clone();
+        full_path.extend(&folder_names);
+        let is_folder = match self.file_tree.find_path(&full_path) {
+            _ if folder_names.is_empty() => true,
+            Some(FileOrFolder::Folder(_)) => true,
+            Some(FileOrFolder::File(_)) => false,
+            None => {
+                if let UiMode::PathPrompt(path_prompt) = &mut self.ui_mode {
+                    path_prompt.not_found = true;
+                }
+                self.render();
+                return;
+            }
+        };
+        self.ui_mode = UiMode::Normal;
+        if is_folder {
+            self.enter_path(&folder_names);
+            self.render_and_update_board();
+        } else {
+            // a file is selected in the folder it's in
+            self.jump_to(&folder_names);
+        }
+    }
+    pub fn start_search(&mut self) {
+        self.clear_search_filter();
+        self.ui_mode = UiMode::Search(Search::new());
+        self.render_and_update_board();
+    }
+    fn update_search(&mut self, change: impl FnOnce(&mut Search)) {
+        if let UiMode::Search(search) = &mut self.ui_mode {
+            change(search);
+            search.update();
+            let results_pending = if search.results_pending {
+                Some(search.changes)
+            } else {
+                None
+            };
+            // the current folder is filtered as we type, unless we're searching everywhere
+            let name_filter = if search.is_recursive {
+                None
+            } else {
+                search.name_filter.clone()
+            };
+            if let Some(changes) = results_pending {
+                self.find_search_results_later(changes);
+            }
+            self.board.change_name_filter(name_filter);
+            self.board.reset_zoom_index();
+            self.board.reset_selected_index();
+            self.render_and_update_board();
+        }
+    }
+    fn find_search_results_later(&self, changes: u64) {
+        // searching everywhere walks the whole file tree, so we wait until
+        // the user stops typing before doing it
+        let instruction_sender = self.instruction_sender.clone();
+        thread::Builder::new()
+            .name("search_debouncer".to_string())
+            .spawn(move || {
+                park_timeout(SEARCH_DEBOUNCE);
+                let _ = instruction_sender.send(Instruction::FindSearchResults(changes));
+            })
+            .expect("failed to spawn search debouncer thread");
+    }
+    pub fn find_search_results(&mut self, changes: u64) {
+        if let UiMode::Search(search) = &mut self.ui_mode {
+            // the query changed again since, that change will find its own results
+            if search.changes == changes {
+                search.find_results(self.file_tree.get_base_folder());
+                self.render();
+            }
+        }
+    }
+    pub fn add_to_search(&mut self, character: char) {
+        self.update_search(|search| search.query.push(character));
+    }
+    pub fn remove_from_search(&mut self) {
+        self.update_search(|search| {
+            search.query.pop();
+        });
+    }
+    pub fn toggle_search_regex(&mut self) {
+        self.update_search(|search| search.is_regex = !search.is_regex);
+    }
+    pub fn toggle_recursive_search(&mut self) {
+        self.update_search(|search| search.is_recursive = !search.is_recursive);
+    }
+    pub fn select_next_search_result(&mut self) {
+        if let UiMode::Search(search) = &mut self.ui_mode {
+            search.select_next();
+            self.render();
+        }
+    }
+    pub fn select_previous_search_result(&mut self) {
+        if let UiMode::Search(search) = &mut self.ui_mode {
+            search.select_previous();
+            self.render();
+        }
+    }
+    pub fn confirm_search(&mut self) {
+        let search = match &mut self.ui_mode {
+            UiMode::Search(search) => search,
+            _ => return,
+        };
+        if search.is_recursive {
+            // <ENTER> was pressed before we got to search
+            search.find_results(self.file_tree.get_base_folder());
+            let path_to_file = match search.selected_result() {
+                Some(selected_result) => selected_result.path_to_file.clone(),
+                None => return,
+            };
+            self.ui_mode = UiMode::Normal;
+            self.jump_to(&path_to_file);
+        } else {
+            // the filter stays until <ESC> is pressed or we leave the folder
+            self.ui_effects.search_filter =
+                search.name_filter.as_ref().map(|_| search.query.clone());
+            self.ui_mode = UiMode::Normal;
+            self.render_and_update_board();
+        }
+    }
+    pub fn cancel_search(&mut self) {
+        self.clear_search_filter();
+        self.board.reset_zoom_index();
+        self.board.reset_selected_index();
+        self.normal_mode();
+    }
+    fn clear_search_filter(&mut self) {
+        self.board.change_name_filter(None);
+        self.ui_effects.search_filter = None;
+    }
+    fn selected_path(&self) -> Option<Vec<OsString>> {
         let currently_selected = self.board.currently_selected()?;
         let mut path_to_file = self.file_tree.current_folder_names.clone();
-        path_to_file.push(currently_selected.name.clone());
+        match &self.board.view {
+            View::Folder => path_to_file.push(currently_selected.name.clone()),
+            View::Categories | View::Owners(_) => return None,
+            View::Category(_) | View::LargestFiles => path_to_file.extend(
+                Path::new(&currently_selected.name)
+                    .iter()
+                    .map(|name| name.to_os_string()),
+            ),
+        }
+        Some(path_to_file)
+    }
+    pub fn get_file_to_delete(&self) -> Option<FileToDelete> {
+        let currently_selected = self.board.currently_selected()?;
+        let path_to_file = self.selected_path()?;
+        // when filtering by owner, a folder's tile only counts some of what is in it
+        let (num_descendants, size) = match self.board.owner_filter() {
+            Some(_) => match self
+                .file_tree
+                .get_base_folder()
+                .path(path_to_file.clone())?
+            {
+                FileOrFolder::Folder(folder) => (Some(folder.num_descendants), folder.size),
+                FileOrFolder::File(file) => (None, file.size),
+            },
+            None => (currently_selected.descendants, currently_selected.size),
+        };
         let file_to_delete = FileToDelete {
             path_in_filesystem: self.file_tree.path_in_filesystem.clone(),
             path_to_file,
             file_type: currently_selected.file_type,
-            num_descendants: currently_selected.descendants,
-            size: currently_selected.size,
+            num_descendants,
+            size,
+            move_to_trash: self.move_to_trash,
         };
         Some(file_to_delete)
     }
     pub fn prompt_file_deletion(&mut self) {
+        if self.refuse_deletion() {
+            return;
+        }
+        if !self.marked_files.is_empty() {
+            let files_to_delete = self.marked_files_to_delete();
+            for file_to_delete in &files_to_delete {
+                if self.refuse_protected_file(file_to_delete) {
+                    return;
+                }
+            }
+            self.ui_mode = UiMode::DeleteMarkedFiles(files_to_delete.clone());
+            if self.delete_confirmation_disabled {
+                self.delete_marked_files(&files_to_delete);
+            } else {
+                self.render();
+            }
+            return;
+        }
         if let Some(file_to_delete) = self.get_file_to_delete() {
+            if self.refuse_protected_file(&file_to_delete) {
+                return;
+            }
             self.ui_mode = UiMode::DeleteFile(file_to_delete.clone());
 
             if self.delete_confirmation_disabled {
@@ -228,45 +922,398 @@ where
             }
         }
     }
+    pub fn toggle_move_to_trash(&mut self, mut file_to_delete: FileToDelete) {
+        if self.dry_run_script.is_some() {
+            return;
+        }
+        file_to_delete.move_to_trash = !file_to_delete.move_to_trash;
+        self.ui_mode = UiMode::DeleteFile(file_to_delete);
+        self.render();
+    }
+    pub fn toggle_marked_files_move_to_trash(&mut self, mut files_to_delete: Vec<FileToDelete>) {
+        if self.dry_run_script.is_some() {
+            return;
+        }
+        for file_to_delete in files_to_delete.iter_mut() {
+            file_to_delete.move_to_trash = !file_to_delete.move_to_trash;
+        }
+        self.ui_mode = UiMode::DeleteMarkedFiles(files_to_delete);
+        self.render();
+    }
+    fn refuse_deletion(&mut self) -> bool {
+        let reason = if self.read_only {
+            "Cannot delete in read-only mode"
+        } else if self.file_tree.is_diff() {
+            "Cannot delete while comparing scans"
+        } else {
+            return false;
+        };
+        self.ui_mode = UiMode::ErrorMessage(String::from(reason));
+        self.render();
+        true
+    }
+    fn refuse_protected_file(&mut self, file_to_delete: &FileToDelete) -> bool {
+        // this is checked even if deletion confirmation is disabled
+        let full_path = file_to_delete.full_path();
+        let error_message = if let Some(rule) = self.protected_paths.protected_by(&full_path) {
+            format!(
+                "Cannot delete {}, it is protected ({})",
+                full_path.to_string_lossy(),
+                rule
+            )
+        } else if let Some((protected_path, rule)) =
+            self.protected_paths.protected_within(&full_path)
+        {
+            format!(
+                "Cannot delete {}, it contains {} which is protected ({})",
+                full_path.to_string_lossy(),
+                protected_path.to_string_lossy(),
+                rule
+            )
+        } else {
+            return false;
+        };
+        self.ui_mode = UiMode::ErrorMessage(error_message);
+        self.render();
+        true
+    }
+    pub fn toggle_mark(&mut self) {
+        if self.refuse_deletion() {
+            return;
+        }
+        if let Some(file_to_mark) = self.get_file_to_delete() {
+            if self.refuse_protected_file(&file_to_mark) {
+                return;
+            }
+            let marked_count = self.marked_files.len();
+            self.marked_files
+                .retain(|marked_file| marked_file.path_to_file != file_to_mark.path_to_file);
+            if self.marked_files.len() == marked_count {
+                self.marked_files.push(file_to_mark);
+            }
+            self.render_and_update_board();
+        }
+    }
+    fn marked_files_to_delete(&self) -> Vec<FileToDelete> {
+        // files in marked folders are deleted along with their folder
+        self.marked_files
+            .iter()
+            .filter(|marked_file| {
+                !self.marked_files.iter().any(|other| {
+                    other.path_to_file.len() < marked_file.path_to_file.len()
+                        && marked_file.path_to_file.starts_with(&other.path_to_file)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+    pub fn show_help(&mut self) {
+        self.ui_mode = UiMode::Help;
+        self.render();
+    }
     pub fn normal_mode(&mut self) {
         self.ui_mode = UiMode::Normal;
         self.render_and_update_board();
     }
     pub fn delete_file(&mut self, file_to_delete: &FileToDelete) {
+        self.delete_files(slice::from_ref(file_to_delete));
+    }
+    pub fn delete_marked_files(&mut self, files_to_delete: &[FileToDelete]) {
+        self.delete_files(files_to_delete);
+    }
+    pub fn is_deleting(&self) -> bool {
+        self.deletion.is_some()
+    }
+    fn delete_files(&mut self, files_to_delete: &[FileToDelete]) {
+        if self.refuse_deletion() || self.is_deleting() {
+            return;
+        }
+        let mut deletion = Deletion::new(self.ui_mode.clone(), files_to_delete.len());
         self.ui_effects.deletion_in_progress = true;
         self.render();
-        self.ui_effects.deletion_in_progress = false;
 
+        for file_to_delete in files_to_delete {
+            match self.delete_from_disk(file_to_delete) {
+                Ok(Some(outcome)) => {
+                    self.record_in_audit_log(file_to_delete, Ok(outcome));
+                    self.remove_file_from_ui(file_to_delete);
+                    deletion.deleted += 1;
+                }
+                // these have to be deleted permanently, which is done in the background
+                Ok(None) => deletion.in_progress.push(file_to_delete.clone()),
+                Err(msg) => {
+                    self.record_in_audit_log(file_to_delete, Err(&msg));
+                    deletion.add_error(file_to_delete, msg);
+                }
+            }
+        }
+        if deletion.in_progress.is_empty() {
+            self.finish_deletion(deletion);
+            return;
+        }
+        // what we know of them from the scan lets us delete their files in parallel
+        let subtrees = deletion
+            .in_progress
+            .iter()
+            .map(|file_to_delete| {
+                let full_path = file_to_delete.full_path();
+                let scanned = self.file_tree.find_path(&full_path);
+                Subtree::new(full_path, scanned)
+            })
+            .collect();
+        self.remove_in_background(deletion, subtrees);
+    }
+    fn purge_deleted_files(&mut self) -> bool {
+        // files deleted permanently were kept in the trash so that they could be
+        // restored, now they are removed from it for good (or, if this is cancelled,
+        // left there for the user to deal with)
+        let mut files_to_delete = vec![];
+        let mut purged = vec![];
+        let mut subtrees = vec![];
+        for deleted_file in self.deleted_files.drain(..) {
+            if let Some(subtree) = deleted_file.staged {
+                files_to_delete.push(deleted_file.file_to_delete);
+                purged.push(deleted_file.trashed_file);
+                subtrees.push(subtree);
+            }
+        }
+        if subtrees.is_empty() {
+            return false;
+        }
+        self.ui_mode = UiMode::DeleteMarkedFiles(files_to_delete.clone());
+        let mut deletion = Deletion::new(self.ui_mode.clone(), subtrees.len());
+        deletion.in_progress = files_to_delete;
+        deletion.purged = purged;
+        self.ui_effects.deletion_in_progress = true;
+        self.remove_in_background(deletion, subtrees);
+        true
+    }
+    fn remove_in_background(&mut self, deletion: Deletion, subtrees: Vec<Subtree>) {
+        let files_to_remove = deletion
+            .in_progress
+            .iter()
+            .map(|file_to_delete| file_to_delete.num_descendants.unwrap_or(0) + 1)
+            .sum();
+        let show_apparent_size = self.file_tree.show_apparent_size;
+        let instruction_sender = self.instruction_sender.clone();
+        let cancelled = deletion.cancelled.clone();
+        self.ui_effects.deletion_progress = Some((DeletionProgress::default(), files_to_remove));
+        self.deletion = Some(deletion);
+        self.render();
+        thread::Builder::new()
+            .name("deleter".to_string())
+            .spawn(move || {
+                remove_paths(subtrees, show_apparent_size, instruction_sender, cancelled);
+            })
+            .expect("failed to start deleting files");
+    }
+    pub fn update_deletion_progress(&mut self, progress: DeletionProgress) {
+        if let Some((deletion_progress, _)) = self.ui_effects.deletion_progress.as_mut() {
+            *deletion_progress = progress;
+            self.render();
+        }
+    }
+    pub fn cancel_deletion(&mut self) {
+        // the worker stops at the next file, and then tells us what it managed to remove
+        if let Some(deletion) = self.deletion.as_ref() {
+            deletion.cancelled.store(true, Ordering::Release);
+        }
+    }
+    pub fn finish_background_deletion(&mut self, removals: Vec<Removal>) {
+        let mut deletion = match self.deletion.take() {
+            Some(deletion) => deletion,
+            None => return,
+        };
+        if !deletion.purged.is_empty() {
+            self.finish_purge(deletion, removals);
+            return;
+        }
+        let files_to_delete = mem::take(&mut deletion.in_progress);
+        for (file_to_delete, removal) in files_to_delete.iter().zip(removals) {
+            match removal {
+                Removal::Removed => {
+                    self.record_in_audit_log(file_to_delete, Ok(Outcome::Deleted));
+                    self.remove_deleted_file_from_ui(file_to_delete);
+                    deletion.deleted += 1;
+                }
+                Removal::Failed(msg) => {
+                    self.record_in_audit_log(file_to_delete, Err(&msg));
+                    self.remove_partially_deleted_file_from_ui(file_to_delete);
+                    deletion.add_error(file_to_delete, msg);
+                }
+                Removal::Cancelled => {
+                    self.record_in_audit_log(file_to_delete, Err("Deletion was cancelled"));
+                    self.remove_partially_deleted_file_from_ui(file_to_delete);
+                }
+            }
+        }
+        self.ui_effects.deletion_progress = None;
+        self.finish_deletion(deletion);
+    }
+    fn finish_purge(&mut self, deletion: Deletion, removals: Vec<Removal>) {
+        let purged = deletion.in_progress.iter().zip(&deletion.purged);
+        for ((file_to_delete, trashed_file), removal) in purged.zip(removals) {
+            match removal {
+                Removal::Removed => {
+                    let _ = trashed_file.remove_trash_info();
+                }
+                // whatever we could not remove is left in the trash
+                Removal::Failed(_) | Removal::Cancelled => {
+                    self.record_in_audit_log(file_to_delete, Ok(Outcome::MovedToTrash));
+                }
+            }
+        }
+        self.ui_effects.deletion_in_progress = false;
+        self.ui_effects.deletion_progress = None;
+        self.exit();
+    }
+    fn finish_deletion(&mut self, deletion: Deletion) {
+        self.ui_effects.deletion_in_progress = false;
+        // the files we failed to delete stay marked
+        self.ui_mode = match deletion.errors.as_slice() {
+            [] => UiMode::Normal,
+            [(_, msg)] if deletion.total == 1 => UiMode::ErrorMessage(msg.clone()),
+            errors => {
+                let mut error_message = format!(
+                    "Failed to delete {} of {} marked items",
+                    errors.len(),
+                    deletion.total
+                );
+                for (full_path, msg) in errors {
+                    error_message.push('\n');
+                    error_message.push_str(&format!("{}: {}", full_path, msg));
+                }
+                UiMode::ErrorMessage(error_message)
+            }
+        };
+        self.render_and_update_board();
+        if deletion.deleted > 0 {
+            let _ = self.event_sender.try_send(Event::FileDeleted);
+        }
+        if let UiMode::Normal = self.ui_mode {
+            self.show_audit_log_error();
+        }
+    }
+    fn record_in_audit_log(
+        &mut self,
+        file_to_delete: &FileToDelete,
+        outcome: Result<Outcome, &str>,
+    ) {
+        if let Some(audit_log) = self.audit_log.as_mut() {
+            if let Err(msg) = audit_log.record(file_to_delete, outcome) {
+                // this is only shown once the deletion itself is done
+                self.audit_log_error = Some(format!("Failed to write audit log: {}", msg));
+            }
+        }
+    }
+    fn show_audit_log_error(&mut self) {
+        if let Some(audit_log_error) = self.audit_log_error.take() {
+            self.ui_mode = UiMode::ErrorMessage(audit_log_error);
+            self.render();
+        }
+    }
+    fn delete_from_disk(
+        &mut self,
+        file_to_delete: &FileToDelete,
+    ) -> Result<Option<Outcome>, String> {
+        // returns None if the file has to be removed permanently, which can
+        // take a long while and so is left for remove_paths
         let full_path = file_to_delete.full_path();
-        match fs::metadata(&full_path) {
-            Ok(metadata) => {
-                let file_type = metadata.file_type();
-                let file_removed = if file_type.is_dir() {
-                    fs::remove_dir_all(&full_path)
-                } else {
-                    fs::remove_file(&full_path)
-                };
-                match file_removed {
-                    Ok(_) => {
-                        self.remove_file_from_ui(file_to_delete);
-                        self.ui_mode = UiMode::Normal;
-                        self.render_and_update_board();
-                        let _ = self.event_sender.try_send(Event::FileDeleted);
-                    }
-                    Err(msg) => {
-                        self.ui_mode = UiMode::ErrorMessage(format!("{}", msg));
-                        self.render();
-                    }
-                };
+        if let Some(dry_run_script) = self.dry_run_script.as_mut() {
+            // the file is only removed from the ui, to be deleted when the script is run
+            return dry_run_script
+                .record_deletion(&full_path)
+                .map(|_| Some(Outcome::DryRun))
+                .map_err(|msg| format!("Failed to write dry run script: {}", msg));
+        }
+        let metadata = fs::metadata(&full_path).map_err(|msg| format!("{}", msg))?;
+        if metadata.is_dir() != (file_to_delete.file_type == FileType::Folder) {
+            // the file tree might be out of date (eg. if it was loaded
+            // from a snapshot), so we make sure we're deleting the
+            // same kind of thing the user saw on screen
+            return Err(format!(
+                "{} changed since it was scanned",
+                full_path.to_string_lossy()
+            ));
+        }
+        if file_to_delete.move_to_trash {
+            let trashed_file = move_to_trash(&full_path).map_err(|msg| format!("{}", msg))?;
+            self.deleted_files.push(DeletedFile {
+                file_to_delete: file_to_delete.clone(),
+                trashed_file,
+                staged: None,
+            });
+            return Ok(Some(Outcome::MovedToTrash));
+        }
+        // files deleted permanently are kept in the trash until we exit so that
+        // the deletion can be undone (and so that they are not lost if we crash),
+        // those that can't be moved there are deleted right away
+        match move_to_trash(&full_path) {
+            Ok(trashed_file) => {
+                let scanned = self.file_tree.find_path(&full_path);
+                let staged = Subtree::new(trashed_file.trashed_path.clone(), scanned);
+                self.deleted_files.push(DeletedFile {
+                    file_to_delete: file_to_delete.clone(),
+                    trashed_file,
+                    staged: Some(staged),
+                });
+                Ok(Some(Outcome::Deleted))
             }
-            Err(msg) => {
-                self.ui_mode = UiMode::ErrorMessage(format!("{}", msg));
-                self.render();
+            Err(_) => Ok(None),
+        }
+    }
+    pub fn undo_deletion(&mut self) {
+        let deleted_file = match self.deleted_files.pop() {
+            Some(deleted_file) => deleted_file,
+            None => return,
+        };
+        let trashed_file = &deleted_file.trashed_file;
+        if let Err(msg) = trashed_file.restore() {
+            self.ui_mode = UiMode::ErrorMessage(format!(
+                "Failed to restore {}: {}",
+                trashed_file.original_path.to_string_lossy(),
+                msg
+            ));
+            // so that it can be restored once whatever is in the way is moved
+            self.deleted_files.push(deleted_file);
+            self.render();
+            return;
+        }
+        let deleted_file = &deleted_file.file_to_delete;
+        self.record_in_audit_log(deleted_file, Ok(Outcome::Restored));
+        self.add_path_to_file_tree(&deleted_file.full_path());
+        self.file_tree.space_freed = self.file_tree.space_freed.saturating_sub(deleted_file.size);
+        self.render_and_update_board();
+        self.show_audit_log_error();
+    }
+    fn add_path_to_file_tree(&mut self, path: &Path) {
+        // adds whatever is on disk at path (and beneath it) back to the file tree
+        let root = self.file_tree.path_in_filesystem.clone();
+        for entry in walk_subfolder(path, &root, &self.scan_filter)
+            .into_iter()
+            .flatten()
+        {
+            match scan_state(&entry) {
+                // these were not counted again when the folder was deleted
+                ScanState::Excluded => {}
+                ScanState::MountPoint => {
+                    self.file_tree.add_mount_point(&entry.path());
+                    self.protected_paths.add_scanned(&entry.path());
+                }
+                ScanState::Scanned => {
+                    if let Ok(file_metadata) = entry.metadata() {
+                        self.file_tree.update_entry(&file_metadata, &entry.path());
+                        self.protected_paths.add_scanned(&entry.path());
+                    }
+                }
             }
         }
     }
-    pub fn increment_failed_to_read(&mut self) {
-        self.file_tree.failed_to_read += 1;
+    pub fn increment_failed_to_read(&mut self, folder: &Path) {
+        self.file_tree.add_failed_to_read(folder);
+    }
+    pub fn increment_excluded(&mut self, folder: &Path) {
+        self.file_tree.add_excluded(folder);
     }
     pub fn zoom_in(&mut self) {
         let current_folder = self.file_tree.get_current_folder();
@@ -284,8 +1331,43 @@ where
         self.render();
     }
     fn remove_file_from_ui(&mut self, file_to_delete: &FileToDelete) {
+        self.protected_paths
+            .remove_scanned(&file_to_delete.full_path());
         self.file_tree.space_freed += file_to_delete.size;
         self.file_tree.delete_file(file_to_delete);
+        self.forget_deleted_marks(file_to_delete);
+    }
+    fn remove_deleted_file_from_ui(&mut self, file_to_delete: &FileToDelete) {
+        // while the file was deleted in the background, it might have
+        // already been removed from the file tree by the watcher
+        self.file_tree.space_freed += file_to_delete.size;
+        self.protected_paths
+            .remove_scanned(&file_to_delete.full_path());
+        self.file_tree.remove_entry(&file_to_delete.full_path());
+        self.forget_deleted_marks(file_to_delete);
+    }
+    fn remove_partially_deleted_file_from_ui(&mut self, file_to_delete: &FileToDelete) {
+        // we count what is left of it on disk again, and the rest is freed
+        let full_path = file_to_delete.full_path();
+        self.protected_paths.remove_scanned(&full_path);
+        self.file_tree.remove_entry(&full_path);
+        let size_without_file = self.file_tree.get_total_size();
+        self.add_path_to_file_tree(&full_path);
+        let size_left = self.file_tree.get_total_size() - size_without_file;
+        self.file_tree.space_freed += file_to_delete.size.saturating_sub(size_left);
+        // only the marks of files that are still there are kept
+        let deleted_path = &file_to_delete.path_to_file;
+        self.marked_files.retain(|marked_file| {
+            !marked_file.path_to_file.starts_with(deleted_path)
+                || fs::symlink_metadata(marked_file.full_path()).is_ok()
+        });
+        self.board.reset_selected_index();
+    }
+    fn forget_deleted_marks(&mut self, file_to_delete: &FileToDelete) {
+        // anything marked in a deleted folder is gone as well
+        let deleted_path = &file_to_delete.path_to_file;
+        self.marked_files
+            .retain(|marked_file| !marked_file.path_to_file.starts_with(deleted_path));
         self.board.reset_selected_index();
     }
 }
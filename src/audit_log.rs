@@ -0,0 +1,205 @@
+// with --audit-log, every deletion (and every failed attempt) is appended to
+// a file as a line of json, so that it is known afterwards who removed what
+use ::std::env;
+use ::std::fs::{File, OpenOptions};
+use ::std::io::{self, Write};
+use ::std::path::Path;
+use ::std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(target_os = "windows"))]
+use ::nix::unistd::getuid;
+
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::user_name;
+use crate::report::{escape_json, file_type_name};
+use crate::state::FileToDelete;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Outcome {
+    Deleted,
+    MovedToTrash,
+    // written to the --dry-run script rather than deleted
+    DryRun,
+    // a deletion that was undone
+    Restored,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Deleted => "deleted",
+            Outcome::MovedToTrash => "moved_to_trash",
+            Outcome::DryRun => "dry_run",
+            Outcome::Restored => "restored",
+        }
+    }
+}
+
+pub struct AuditLog {
+    file: File,
+    user: String,
+    // whoever ran sudo, which we have no way to check, so it is only logged next to the user
+    sudo_user: Option<String>,
+}
+
+impl AuditLog {
+    pub fn open(log_path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?;
+        Ok(AuditLog {
+            file,
+            user: current_user(),
+            sudo_user: env::var("SUDO_USER").ok().filter(|user| !user.is_empty()),
+        })
+    }
+    pub fn record(
+        &mut self,
+        file_to_delete: &FileToDelete,
+        outcome: Result<Outcome, &str>,
+    ) -> io::Result<()> {
+        let (outcome, error) = match outcome {
+            Ok(outcome) => (outcome.name(), String::from("null")),
+            Err(msg) => ("failed", format!("\"{}\"", escape_json(msg))),
+        };
+        let sudo_user = match &self.sudo_user {
+            Some(sudo_user) => format!("\"{}\"", escape_json(sudo_user)),
+            None => String::from("null"),
+        };
+        let descendants = match file_to_delete.num_descendants {
+            Some(num_descendants) => num_descendants.to_string(),
+            None => String::from("null"),
+        };
+        let line = format!(
+            "{{\"timestamp\":\"{}\",\"user\":\"{}\",\"sudo_user\":{},\"path\":\"{}\",\"type\":\"{}\",\"size\":{},\"descendants\":{},\"outcome\":\"{}\",\"error\":{}}}\n",
+            utc_timestamp(SystemTime::now()),
+            escape_json(&self.user),
+            sudo_user,
+            escape_json(&file_to_delete.full_path().to_string_lossy()),
+            file_type_name(file_to_delete.file_type),
+            file_to_delete.size,
+            descendants,
+            outcome,
+            error,
+        );
+        // a single write, so that lines from several diskonauts logging
+        // to the same file do not get mixed up
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn current_user() -> String {
+    // the user we really run as, since the environment can say anything
+    let uid = getuid().as_raw();
+    user_name(uid).unwrap_or_else(|| uid.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn current_user() -> String {
+    env::var("USERNAME")
+        .ok()
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, seconds_in_day) = (seconds / 86400, seconds % 86400);
+    // the number of days since 1970-01-01 as a date, from:
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_in_day / 3600,
+        seconds_in_day % 3600 / 60,
+        seconds_in_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::ffi::OsString;
+    use ::std::fs::{create_dir_all, read_to_string, remove_file};
+    use ::std::path::PathBuf;
+    use ::std::time::Duration;
+
+    use crate::state::tiles::FileType;
+
+    #[test]
+    fn format_utc_timestamps() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400 + 3661)),
+            "2000-02-29T01:01:01Z"
+        );
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_798_761_599)),
+            "2026-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn record_deletions() {
+        let folder = PathBuf::from("/tmp/diskonaut_tests/audit_log");
+        create_dir_all(&folder).expect("failed to create folder");
+        let log_path = folder.join("audit.log");
+        remove_file(&log_path).ok();
+
+        let file_to_delete = FileToDelete {
+            path_in_filesystem: PathBuf::from("/tmp"),
+            path_to_file: vec![OsString::from("some \"folder\"")],
+            file_type: FileType::Folder,
+            num_descendants: Some(2),
+            size: 8192,
+            move_to_trash: false,
+        };
+        let mut audit_log = AuditLog::open(&log_path).expect("failed to open audit log");
+        audit_log.user = String::from("root");
+        audit_log.sudo_user = Some(String::from("someone"));
+        audit_log
+            .record(&file_to_delete, Ok(Outcome::Deleted))
+            .expect("failed to record deletion");
+        audit_log.user = String::from("someone");
+        audit_log.sudo_user = None;
+        audit_log
+            .record(&file_to_delete, Err("Permission denied"))
+            .expect("failed to record deletion");
+
+        let contents = read_to_string(&log_path).expect("failed to read audit log");
+        remove_file(&log_path).ok();
+        let lines: Vec<&str> = contents
+            .lines()
+            .map(|line| &line[line.find("\"user\"").expect("missing user")..])
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "\"user\":\"root\",\"sudo_user\":\"someone\",\"path\":\"/tmp/some \\\"folder\\\"\",\"type\":\"folder\",\"size\":8192,\"descendants\":2,\"outcome\":\"deleted\",\"error\":null}",
+                "\"user\":\"someone\",\"sudo_user\":null,\"path\":\"/tmp/some \\\"folder\\\"\",\"type\":\"folder\",\"size\":8192,\"descendants\":2,\"outcome\":\"failed\",\"error\":\"Permission denied\"}",
+            ]
+        );
+    }
+}
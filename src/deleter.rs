@@ -0,0 +1,357 @@
+// permanently deleting a large folder can take a long while, so it is done
+// in its own thread which lets the app know how far along it is
+use ::std::collections::HashSet;
+use ::std::fs;
+use ::std::io;
+use ::std::path::{Path, PathBuf};
+use ::std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use ::std::sync::mpsc::SyncSender;
+use ::std::sync::{Arc, Mutex};
+use ::std::time::{Duration, Instant};
+
+use ::filesize::PathExt;
+use ::rayon::prelude::*;
+
+use crate::messages::Instruction;
+use crate::state::files::FileOrFolder;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeletionProgress {
+    // files and folders
+    pub removed: u64,
+    pub space_freed: u128,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Removal {
+    Removed,
+    Failed(String),
+    // the deletion was cancelled before this was (completely) removed
+    Cancelled,
+}
+
+// a file or folder to delete, along with what we know of it from the scan
+pub struct Subtree {
+    path: PathBuf,
+    // the scanned files in it and their sizes (or the path itself, if it is a file)
+    files: Vec<(PathBuf, u128)>,
+    // the scanned folders in it grouped by depth, deepest first
+    folders: Vec<Vec<PathBuf>>,
+}
+
+impl Subtree {
+    pub fn new(path: PathBuf, scanned: Option<&FileOrFolder>) -> Self {
+        let mut subtree = Subtree {
+            path: path.clone(),
+            files: vec![],
+            folders: vec![],
+        };
+        if let Some(scanned) = scanned {
+            subtree.add(path, scanned, 0);
+        }
+        subtree.folders.reverse();
+        subtree
+    }
+    fn add(&mut self, path: PathBuf, scanned: &FileOrFolder, depth: usize) {
+        match scanned {
+            FileOrFolder::File(file) => self.files.push((path, file.size)),
+            FileOrFolder::Folder(folder) => {
+                if self.folders.len() == depth {
+                    self.folders.push(vec![]);
+                }
+                // the contents of mount points were not scanned
+                if !folder.is_mount_point {
+                    for (name, scanned) in &folder.contents {
+                        self.add(path.join(name), scanned, depth + 1);
+                    }
+                }
+                self.folders[depth].push(path);
+            }
+        }
+    }
+}
+
+fn still_folders(subtree: &Subtree) -> HashSet<&Path> {
+    // the scan might be out of date, so a folder we scanned might since have been
+    // replaced by a symlink, nothing beneath it can be removed without following
+    // the link - so we only keep folders that still are folders, as are their parents
+    let mut folders = HashSet::new();
+    for path in subtree.folders.iter().rev().flatten() {
+        let parent_kept = *path == subtree.path
+            || matches!(path.parent(), Some(parent) if folders.contains(parent));
+        let is_folder = fs::symlink_metadata(path)
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+        if parent_kept && is_folder {
+            folders.insert(path.as_path());
+        }
+    }
+    folders
+}
+
+fn is_in_folders(path: &Path, root: &Path, folders: &HashSet<&Path>) -> bool {
+    // the root itself is whatever is there now, and remove_file does not follow symlinks
+    path == root || matches!(path.parent(), Some(parent) if folders.contains(parent))
+}
+
+struct Remover<'a> {
+    instruction_sender: &'a SyncSender<Instruction>,
+    cancelled: &'a AtomicBool,
+    show_apparent_size: bool,
+    removed: AtomicU64,
+    space_freed: AtomicU64,
+    last_sent: Mutex<Instant>,
+}
+
+impl<'a> Remover<'a> {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+    fn count_removed(&self, size: u128) {
+        self.removed.fetch_add(1, Ordering::Relaxed);
+        self.space_freed.fetch_add(size as u64, Ordering::Relaxed);
+        // whichever thread gets here first sends the progress, the rest move on
+        if let Ok(mut last_sent) = self.last_sent.try_lock() {
+            if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                let progress = DeletionProgress {
+                    removed: self.removed.load(Ordering::Relaxed),
+                    space_freed: self.space_freed.load(Ordering::Relaxed) as u128,
+                };
+                let _ = self
+                    .instruction_sender
+                    .send(Instruction::UpdateDeletionProgress(progress));
+                *last_sent = Instant::now();
+            }
+        }
+    }
+    fn remove_in_parallel(&self, subtree: &Subtree) {
+        // errors are ignored here, anything left over is removed (or
+        // reported) by remove, along with whatever was not scanned
+        let folders = still_folders(subtree);
+        subtree.files.par_iter().for_each(|(path, size)| {
+            if !self.is_cancelled()
+                && is_in_folders(path, &subtree.path, &folders)
+                && fs::remove_file(path).is_ok()
+            {
+                self.count_removed(*size);
+            }
+        });
+        // a folder can only be removed once it's empty, so we start with the deepest ones
+        for folders_at_depth in &subtree.folders {
+            folders_at_depth.par_iter().for_each(|path| {
+                if !self.is_cancelled()
+                    && folders.contains(path.as_path())
+                    && fs::remove_dir(path).is_ok()
+                {
+                    self.count_removed(0);
+                }
+            });
+        }
+    }
+    fn remove(&self, path: &Path) -> io::Result<bool> {
+        // returns false if we were cancelled before we were done
+        if self.is_cancelled() {
+            return Ok(false);
+        }
+        let metadata = match fs::symlink_metadata(path) {
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(true),
+            metadata => metadata?,
+        };
+        if metadata.is_dir() {
+            // the folder's contents first, since it can only be removed once it's empty
+            for entry in fs::read_dir(path)? {
+                if !self.remove(&entry?.path())? {
+                    return Ok(false);
+                }
+            }
+            fs::remove_dir(path)?;
+            self.count_removed(0);
+        } else {
+            fs::remove_file(path)?;
+            let size = if self.show_apparent_size {
+                metadata.len()
+            } else {
+                path.size_on_disk_fast(&metadata).unwrap_or(metadata.len())
+            };
+            self.count_removed(size as u128);
+        }
+        Ok(true)
+    }
+    fn remove_subtree(&self, subtree: &Subtree) -> Removal {
+        if let Err(msg) = fs::symlink_metadata(&subtree.path) {
+            return Removal::Failed(format!("{}", msg));
+        }
+        self.remove_in_parallel(subtree);
+        match self.remove(&subtree.path) {
+            Ok(true) => Removal::Removed,
+            Ok(false) => Removal::Cancelled,
+            Err(msg) => Removal::Failed(format!("{}", msg)),
+        }
+    }
+}
+
+pub fn remove_paths(
+    subtrees: Vec<Subtree>,
+    show_apparent_size: bool,
+    instruction_sender: SyncSender<Instruction>,
+    cancelled: Arc<AtomicBool>,
+) {
+    let remover = Remover {
+        instruction_sender: &instruction_sender,
+        cancelled: &cancelled,
+        show_apparent_size,
+        removed: AtomicU64::new(0),
+        space_freed: AtomicU64::new(0),
+        last_sent: Mutex::new(Instant::now()),
+    };
+    let removals = subtrees
+        .iter()
+        .map(|subtree| remover.remove_subtree(subtree))
+        .collect();
+    let _ = instruction_sender.send(Instruction::FinishDeletion(removals));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::fs::{create_dir_all, remove_dir_all, write};
+    use ::std::sync::mpsc;
+
+    use crate::scanner::{scan_subfolder, ScanFilter};
+
+    fn removals(instructions: mpsc::Receiver<Instruction>) -> Vec<Removal> {
+        for instruction in instructions {
+            if let Instruction::FinishDeletion(removals) = instruction {
+                return removals;
+            }
+        }
+        unreachable!("deletion did not finish");
+    }
+
+    #[test]
+    fn remove_files_and_folders() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/deleter_remove");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("folder/subfolder")).expect("failed to create folder");
+        write(path.join("folder/subfolder/file"), vec![0; 1000]).expect("failed to create file");
+        write(path.join("file"), vec![0; 1000]).expect("failed to create file");
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        remove_paths(
+            vec![
+                Subtree::new(path.join("folder"), None),
+                Subtree::new(path.join("file"), None),
+                Subtree::new(path.join("missing"), None),
+            ],
+            true,
+            sender,
+            Arc::new(AtomicBool::new(false)),
+        );
+        let removals = removals(receiver);
+        let files_left = fs::read_dir(&path).expect("failed to read folder").count();
+        remove_dir_all(&path).ok();
+
+        assert_eq!(removals[0], Removal::Removed);
+        assert_eq!(removals[1], Removal::Removed);
+        assert!(matches!(removals[2], Removal::Failed(_)));
+        assert_eq!(files_left, 0);
+    }
+
+    #[test]
+    fn remove_scanned_folder_in_parallel() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/deleter_parallel");
+        remove_dir_all(&path).ok();
+        for i in 0..10 {
+            let subfolder = path.join(format!("folder/subfolder{}/subsubfolder", i));
+            create_dir_all(&subfolder).expect("failed to create folder");
+            write(subfolder.join("file"), vec![0; 1000]).expect("failed to create file");
+        }
+        let file_tree = scan_subfolder(
+            path.join("folder"),
+            &path,
+            true,
+            false,
+            &ScanFilter::default(),
+        );
+        let scanned = FileOrFolder::Folder(file_tree.get_base_folder().clone());
+        // created after the scan, so it is only found once the scanned files are gone
+        write(path.join("folder/subfolder0/unscanned"), vec![0; 1000])
+            .expect("failed to create file");
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        remove_paths(
+            vec![Subtree::new(path.join("folder"), Some(&scanned))],
+            true,
+            sender,
+            Arc::new(AtomicBool::new(false)),
+        );
+        let removals = removals(receiver);
+        let folder_left = path.join("folder").exists();
+        remove_dir_all(&path).ok();
+
+        assert_eq!(removals, vec![Removal::Removed]);
+        assert!(!folder_left);
+    }
+
+    #[test]
+    fn do_not_follow_folders_replaced_by_symlinks() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/deleter_symlink");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("folder/subfolder")).expect("failed to create folder");
+        write(path.join("folder/subfolder/file"), vec![0; 1000]).expect("failed to create file");
+        let file_tree = scan_subfolder(
+            path.join("folder"),
+            &path,
+            true,
+            false,
+            &ScanFilter::default(),
+        );
+        let scanned = FileOrFolder::Folder(file_tree.get_base_folder().clone());
+        // the scanned file's path now leads somewhere else entirely
+        create_dir_all(path.join("elsewhere")).expect("failed to create folder");
+        write(path.join("elsewhere/file"), vec![0; 1000]).expect("failed to create file");
+        remove_dir_all(path.join("folder/subfolder")).expect("failed to remove folder");
+        ::std::os::unix::fs::symlink(path.join("elsewhere"), path.join("folder/subfolder"))
+            .expect("failed to create symlink");
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        remove_paths(
+            vec![Subtree::new(path.join("folder"), Some(&scanned))],
+            true,
+            sender,
+            Arc::new(AtomicBool::new(false)),
+        );
+        let removals = removals(receiver);
+        let folder_left = path.join("folder").exists();
+        let file_elsewhere_left = path.join("elsewhere/file").exists();
+        remove_dir_all(&path).ok();
+
+        assert_eq!(removals, vec![Removal::Removed]);
+        assert!(!folder_left);
+        assert!(file_elsewhere_left);
+    }
+
+    #[test]
+    fn cancel_deletion() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/deleter_cancel");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("folder")).expect("failed to create folder");
+        write(path.join("folder/file"), vec![0; 1000]).expect("failed to create file");
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        remove_paths(
+            vec![Subtree::new(path.join("folder"), None)],
+            true,
+            sender,
+            Arc::new(AtomicBool::new(true)),
+        );
+        let removals = removals(receiver);
+        let folder_left = path.join("folder/file").exists();
+        remove_dir_all(&path).ok();
+
+        assert_eq!(removals, vec![Removal::Cancelled]);
+        assert!(folder_left);
+    }
+}
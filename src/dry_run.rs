@@ -0,0 +1,107 @@
+// with --dry-run, deleted files and folders are written to a shell script
+// (to be reviewed and run later) rather than being deleted
+use ::std::ffi::OsStr;
+use ::std::fs::{File, OpenOptions};
+use ::std::io::{self, Write};
+use ::std::path::Path;
+
+#[cfg(not(target_os = "windows"))]
+use ::std::os::unix::fs::OpenOptionsExt;
+
+pub struct DryRunScript {
+    file: File,
+}
+
+impl DryRunScript {
+    pub fn open(script_path: &Path) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(not(target_os = "windows"))]
+        options.mode(0o755);
+        let mut file = options.open(script_path)?;
+        // we add to scripts from previous runs rather than replacing them
+        if file.metadata()?.len() == 0 {
+            file.write_all(b"#!/bin/sh\n")?;
+        }
+        Ok(DryRunScript { file })
+    }
+    pub fn record_deletion(&mut self, path: &Path) -> io::Result<()> {
+        let mut line = b"rm -rf -- ".to_vec();
+        line.extend(shell_quote(path.as_os_str()));
+        line.push(b'\n');
+        // written right away, so that nothing is lost if we crash
+        self.file.write_all(&line)?;
+        self.file.flush()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn os_str_bytes(os_str: &OsStr) -> Vec<u8> {
+    // file names are not necessarily valid utf8, but the shell doesn't mind
+    use ::std::os::unix::ffi::OsStrExt;
+    os_str.as_bytes().to_vec()
+}
+
+#[cfg(target_os = "windows")]
+fn os_str_bytes(os_str: &OsStr) -> Vec<u8> {
+    os_str.to_string_lossy().into_owned().into_bytes()
+}
+
+fn shell_quote(os_str: &OsStr) -> Vec<u8> {
+    // inside single quotes nothing is special except for the single quote
+    // itself, which we end the quotes for, escape and then quote again
+    let mut quoted = vec![b'\''];
+    for byte in os_str_bytes(os_str) {
+        if byte == b'\'' {
+            quoted.extend(b"'\\''");
+        } else {
+            quoted.push(byte);
+        }
+    }
+    quoted.push(b'\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::fs::{create_dir_all, read_to_string, remove_file};
+    use ::std::path::PathBuf;
+
+    #[test]
+    fn quote_paths_for_the_shell() {
+        assert_eq!(
+            shell_quote(OsStr::new("/tmp/some folder/file")),
+            b"'/tmp/some folder/file'".to_vec()
+        );
+        assert_eq!(
+            shell_quote(OsStr::new("/tmp/it's $HOME/`ls`")),
+            b"'/tmp/it'\\''s $HOME/`ls`'".to_vec()
+        );
+    }
+
+    #[test]
+    fn add_deletions_to_script() {
+        let folder = PathBuf::from("/tmp/diskonaut_tests/dry_run");
+        create_dir_all(&folder).expect("failed to create folder");
+        let script_path = folder.join("cleanup.sh");
+        remove_file(&script_path).ok();
+
+        let mut script = DryRunScript::open(&script_path).expect("failed to open script");
+        script
+            .record_deletion(Path::new("/tmp/file1"))
+            .expect("failed to record deletion");
+        drop(script);
+        let mut script = DryRunScript::open(&script_path).expect("failed to reopen script");
+        script
+            .record_deletion(Path::new("/tmp/folder 2"))
+            .expect("failed to record deletion");
+
+        let contents = read_to_string(&script_path).expect("failed to read script");
+        remove_file(&script_path).ok();
+        assert_eq!(
+            contents,
+            "#!/bin/sh\nrm -rf -- '/tmp/file1'\nrm -rf -- '/tmp/folder 2'\n"
+        );
+    }
+}
@@ -71,7 +71,7 @@ pub fn handle_keypress_loading_mode<B: Backend>(evt: Event, app: &mut App<B>) {
         key!(char '\n') | key!(Enter) => {
             app.handle_enter();
         }
-        key!(Backspace) => {
+        key!(Backspace) if !app.read_only => {
             app.show_warning_modal();
         }
         key!(Esc) => {
@@ -86,9 +86,36 @@ pub fn handle_keypress_normal_mode<B: Backend>(evt: Event, app: &mut App<B>) {
         key!(ctrl 'c') | key!(char 'q') => {
             app.prompt_exit();
         }
-        key!(Backspace) => {
+        key!(Backspace) if !app.read_only => {
             app.prompt_file_deletion();
         }
+        key!(char 'r') => {
+            app.rescan_current_folder();
+        }
+        key!(char 'u') if !app.read_only => {
+            app.undo_deletion();
+        }
+        key!(char ' ') if !app.read_only => {
+            app.toggle_mark();
+        }
+        key!(char '/') => {
+            app.start_search();
+        }
+        key!(char ':') | key!(char 'g') => {
+            app.start_path_prompt();
+        }
+        key!(char 't') => {
+            app.toggle_categories();
+        }
+        key!(char 'f') => {
+            app.toggle_largest_files();
+        }
+        key!(char 'o') => {
+            app.toggle_owners();
+        }
+        key!(char 'a') => {
+            app.toggle_apparent_size();
+        }
         key!(char 'l') | key!(Right) | key!(ctrl 'f') => {
             app.move_selected_right();
         }
@@ -110,6 +137,9 @@ pub fn handle_keypress_normal_mode<B: Backend>(evt: Event, app: &mut App<B>) {
         key!(char '0') => {
             app.reset_zoom();
         }
+        key!(char '?') => {
+            app.show_help();
+        }
         key!(char '\n') | key!(Enter) => {
             app.handle_enter();
         }
@@ -125,6 +155,13 @@ pub fn handle_keypress_delete_file_mode<B: Backend>(
     app: &mut App<B>,
     file_to_delete: FileToDelete,
 ) {
+    if app.is_deleting() {
+        // the only thing we can do while deleting is stop
+        if let key!(Esc) = evt {
+            app.cancel_deletion();
+        }
+        return;
+    }
     match evt {
         key!(ctrl 'c') | key!(char 'q') | key!(Esc) | key!(char 'n') => {
             app.normal_mode();
@@ -132,6 +169,100 @@ pub fn handle_keypress_delete_file_mode<B: Backend>(
         key!(char 'y') => {
             app.delete_file(&file_to_delete);
         }
+        key!(char 't') => {
+            app.toggle_move_to_trash(file_to_delete);
+        }
+        _ => (),
+    };
+}
+
+pub fn handle_keypress_delete_marked_files_mode<B: Backend>(
+    evt: Event,
+    app: &mut App<B>,
+    files_to_delete: Vec<FileToDelete>,
+) {
+    if app.is_deleting() {
+        // the only thing we can do while deleting is stop
+        if let key!(Esc) = evt {
+            app.cancel_deletion();
+        }
+        return;
+    }
+    match evt {
+        key!(ctrl 'c') | key!(char 'q') | key!(Esc) | key!(char 'n') => {
+            app.normal_mode();
+        }
+        key!(char 'y') => {
+            app.delete_marked_files(&files_to_delete);
+        }
+        key!(char 't') => {
+            app.toggle_marked_files_move_to_trash(files_to_delete);
+        }
+        _ => (),
+    };
+}
+
+pub fn handle_keypress_search_mode<B: Backend>(evt: Event, app: &mut App<B>) {
+    match evt {
+        key!(ctrl 'c') | key!(Esc) => {
+            app.cancel_search();
+        }
+        key!(char '\n') | key!(Enter) => {
+            app.confirm_search();
+        }
+        key!(Tab) => {
+            app.toggle_recursive_search();
+        }
+        key!(ctrl 'r') => {
+            app.toggle_search_regex();
+        }
+        key!(Down) | key!(ctrl 'n') => {
+            app.select_next_search_result();
+        }
+        key!(Up) | key!(ctrl 'p') => {
+            app.select_previous_search_result();
+        }
+        key!(Backspace) => {
+            app.remove_from_search();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers: KeyModifiers::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers: KeyModifiers::SHIFT,
+        }) => {
+            app.add_to_search(character);
+        }
+        _ => (),
+    };
+}
+
+pub fn handle_keypress_path_prompt_mode<B: Backend>(evt: Event, app: &mut App<B>) {
+    match evt {
+        key!(ctrl 'c') | key!(Esc) => {
+            app.normal_mode();
+        }
+        key!(char '\n') | key!(Enter) => {
+            app.go_to_path();
+        }
+        key!(Tab) => {
+            app.complete_path_prompt();
+        }
+        key!(Backspace) => {
+            app.remove_from_path_prompt();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers: KeyModifiers::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers: KeyModifiers::SHIFT,
+        }) => {
+            app.add_to_path_prompt(character);
+        }
         _ => (),
     };
 }
@@ -145,6 +276,15 @@ pub fn handle_keypress_error_message<B: Backend>(evt: Event, app: &mut App<B>) {
     };
 }
 
+pub fn handle_keypress_help<B: Backend>(evt: Event, app: &mut App<B>) {
+    match evt {
+        key!(ctrl 'c') | key!(char 'q') | key!(Esc) | key!(char '?') => {
+            app.normal_mode();
+        }
+        _ => (),
+    };
+}
+
 pub fn handle_keypress_screen_too_small<B: Backend>(evt: Event, app: &mut App<B>) {
     match evt {
         key!(ctrl 'c') | key!(char 'q') => {
@@ -1,25 +1,34 @@
 #[cfg(test)]
 mod tests;
 
+mod age;
 mod app;
+mod audit_log;
+mod deleter;
+mod dry_run;
 mod input;
 mod messages;
 mod os;
+mod protected_paths;
+mod report;
+mod scanner;
 mod state;
+mod trash;
 mod ui;
+mod watcher;
 
 use ::failure;
-use ::jwalk::Parallelism::{RayonDefaultPool, Serial};
-use ::jwalk::WalkDir;
 use ::std::env;
-use ::std::io;
-use ::std::path::PathBuf;
+use ::std::fs;
+use ::std::io::{self, Write};
+use ::std::path::{Path, PathBuf};
 use ::std::process;
 use ::std::sync::atomic::{AtomicBool, Ordering};
 use ::std::sync::mpsc;
 use ::std::sync::mpsc::{Receiver, SyncSender};
 use ::std::sync::Arc;
 use ::std::thread::park_timeout;
+use ::std::time::Duration;
 use ::std::{thread, time};
 use ::structopt::StructOpt;
 
@@ -29,22 +38,34 @@ use crossterm::event::{Event as BackEvent, KeyCode, KeyEvent};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use tui::backend::CrosstermBackend;
 
+use age::parse_age;
 use app::{App, UiMode};
+use audit_log::AuditLog;
+use dry_run::DryRunScript;
 use input::TerminalEvents;
 use messages::{handle_events, Event, Instruction};
+use protected_paths::ProtectedPaths;
+use report::{write_report, ReportFormat, ReportOptions};
+use scanner::{read_exclude_file, scan_folder, scan_state, walk_folder, ScanFilter, ScanState};
+use state::files::{diff_file_trees, FileTree, Folder};
+use watcher::{watch_folders, Watcher};
 
 #[cfg(not(test))]
 const SHOULD_SHOW_LOADING_ANIMATION: bool = true;
 #[cfg(test)]
 const SHOULD_SHOW_LOADING_ANIMATION: bool = false;
 #[cfg(not(test))]
-const SHOULD_HANDLE_WIN_CHANGE: bool = true;
-#[cfg(test)]
-const SHOULD_HANDLE_WIN_CHANGE: bool = false;
-#[cfg(not(test))]
 const SHOULD_SCAN_HD_FILES_IN_MULTIPLE_THREADS: bool = true;
 #[cfg(test)]
 const SHOULD_SCAN_HD_FILES_IN_MULTIPLE_THREADS: bool = false;
+#[cfg(not(test))]
+const SHOULD_WATCH_FOLDERS: bool = true;
+#[cfg(test)]
+const SHOULD_WATCH_FOLDERS: bool = false;
+#[cfg(not(test))]
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+#[cfg(test)]
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(0);
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "diskonaut")]
@@ -58,8 +79,124 @@ pub struct Opt {
     #[structopt(short, long)]
     /// Don't ask for confirmation before deleting
     disable_delete_confirmation: bool,
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    /// Save the scanned folder to this file once scanning is done
+    save_snapshot: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "file",
+        conflicts_with = "folder"
+    )]
+    /// Show a snapshot saved with --save-snapshot instead of scanning
+    load_snapshot: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        number_of_values = 2,
+        value_names = &["old", "new"],
+        conflicts_with_all = &["folder", "load-snapshot", "save-snapshot"]
+    )]
+    /// Show how much each file and folder grew between two scans (each either a folder or a snapshot)
+    diff: Vec<PathBuf>,
+    #[structopt(long, value_name = "format", possible_values = &["json", "csv", "text"])]
+    /// Print a report of the scanned folder to stdout instead of starting the ui
+    report: Option<ReportFormat>,
+    #[structopt(long, value_name = "N", requires = "report")]
+    /// Only include files and folders up to N levels below the scanned folder in the report
+    depth: Option<usize>,
+    #[structopt(long, value_name = "N", requires = "report")]
+    /// Only include the N largest files and folders of every folder in the report
+    top: Option<usize>,
+    #[structopt(long, value_name = "glob", number_of_values = 1)]
+    /// Leave files and folders matching this glob out of the scan (can be given more than once)
+    exclude: Vec<String>,
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    /// Read globs to exclude from this file (one per line)
+    exclude_from: Option<PathBuf>,
+    #[structopt(long)]
+    /// Leave files and folders ignored by .gitignore files out of the scan
+    respect_gitignore: bool,
+    #[structopt(short = "x", long)]
+    /// Don't scan folders on other filesystems than the scanned folder (they are shown empty)
+    one_file_system: bool,
+    #[structopt(long, parse(try_from_str = parse_age), value_name = "age")]
+    /// Only count files last modified more than this long ago (eg. 30d or 6mo)
+    older_than: Option<Duration>,
+    #[structopt(long, parse(try_from_str = parse_age), value_name = "age")]
+    /// Only count files last modified less than this long ago (eg. 12h or 2w)
+    newer_than: Option<Duration>,
+    #[structopt(long, parse(try_from_str = parse_age), value_name = "age")]
+    /// Color files and folders nothing in which was modified or accessed for this long (eg. 1y)
+    stale_after: Option<Duration>,
+    #[structopt(short = "l", long)]
+    /// Count the size of hard linked files for every link, rather than only for the first one
+    count_links: bool,
+    #[structopt(long)]
+    /// Move deleted files and folders to the trash rather than deleting them permanently
+    trash: bool,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "script.sh",
+        conflicts_with_all = &["trash", "diff"]
+    )]
+    /// Don't delete anything, add the commands that would delete it to this script instead
+    dry_run: Option<PathBuf>,
+    #[structopt(long, value_name = "path", number_of_values = 1)]
+    /// Refuse to delete this path or files and folders matching this glob (can be given more than once, /, /usr, .git and your home folder are protected unless --no-default-protect is given)
+    protect: Vec<String>,
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    /// Read paths and globs to protect from this file (one per line)
+    protect_from: Option<PathBuf>,
+    #[structopt(long)]
+    /// Don't protect /, /usr, .git and your home folder, only what is given with --protect and --protect-from
+    no_default_protect: bool,
+    #[structopt(
+        long,
+        conflicts_with_all = &["disable-delete-confirmation", "trash", "dry-run"]
+    )]
+    /// Don't allow deleting anything
+    read_only: bool,
+    #[structopt(long, parse(from_os_str), value_name = "file")]
+    /// Add a line to this file for every deletion, with when it happened, who did it and whether it succeeded
+    audit_log: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "relative path",
+        conflicts_with = "report"
+    )]
+    /// Open this folder inside the scanned folder once scanning is done
+    open: Option<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct StartOptions {
+    pub show_apparent_size: bool,
+    pub disable_delete_confirmation: bool,
+    pub move_to_trash: bool,
+    // deleted files and folders are written to this script rather than deleted
+    pub dry_run_script: Option<DryRunScript>,
+    // deletions are recorded in this log
+    pub audit_log: Option<AuditLog>,
+    pub protected_paths: ProtectedPaths,
+    pub read_only: bool,
+    pub save_snapshot: Option<PathBuf>,
+    // the folder (relative to the scanned one) shown once scanning is done
+    pub open_path: Option<PathBuf>,
+    // files and folders untouched for this long are colored differently
+    pub stale_after: Option<Duration>,
+    pub count_hard_links: bool,
+    pub scan_filter: ScanFilter,
+    // a file tree that was already built (eg. loaded from a snapshot),
+    // if this is present we show it instead of scanning the folder
+    pub file_tree: Option<FileTree>,
 }
 
+// exit code of a report that was written but is missing files we failed to read
+const EXIT_CODE_PARTIAL_REPORT: i32 = 1;
+
 fn main() {
     if let Err(err) = try_main() {
         println!("Error: {}", err);
@@ -70,27 +207,177 @@ fn get_stdout() -> io::Result<io::Stdout> {
     Ok(io::stdout())
 }
 
+fn load_snapshot(snapshot_path: &Path) -> Result<FileTree, failure::Error> {
+    match FileTree::load_snapshot(snapshot_path) {
+        Ok(file_tree) => Ok(file_tree),
+        Err(e) => failure::bail!(
+            "Failed to load snapshot '{}': {}",
+            snapshot_path.to_string_lossy(),
+            e
+        ),
+    }
+}
+
+fn get_scan_filter(opts: &Opt) -> Result<ScanFilter, failure::Error> {
+    let mut exclude = opts.exclude.clone();
+    if let Some(exclude_from) = &opts.exclude_from {
+        match read_exclude_file(exclude_from) {
+            Ok(globs) => exclude.extend(globs),
+            Err(e) => failure::bail!("Failed to read '{}': {}", exclude_from.to_string_lossy(), e),
+        }
+    }
+    match ScanFilter::new(&exclude, opts.respect_gitignore) {
+        Ok(scan_filter) => Ok(scan_filter
+            .one_file_system(opts.one_file_system)
+            .modified(opts.older_than, opts.newer_than)),
+        Err(e) => failure::bail!("Invalid exclude pattern: {}", e),
+    }
+}
+
+fn get_protected_paths(opts: &Opt) -> Result<ProtectedPaths, failure::Error> {
+    let mut protect = opts.protect.clone();
+    if let Some(protect_from) = &opts.protect_from {
+        match read_exclude_file(protect_from) {
+            Ok(globs) => protect.extend(globs),
+            Err(e) => failure::bail!("Failed to read '{}': {}", protect_from.to_string_lossy(), e),
+        }
+    }
+    let protected_paths = if opts.no_default_protect {
+        ProtectedPaths::new(&protect)
+    } else {
+        ProtectedPaths::with_defaults(&protect)
+    };
+    match protected_paths {
+        Ok(protected_paths) => Ok(protected_paths),
+        Err(e) => failure::bail!("Invalid protected path: {}", e),
+    }
+}
+
+fn scan_root(folder: PathBuf) -> Result<PathBuf, failure::Error> {
+    // the paths of deleted files are written to the audit log and dry run
+    // script, so they have to make sense wherever those are read
+    if !folder.as_path().is_dir() {
+        failure::bail!("Folder '{}' does not exist", folder.to_string_lossy())
+    }
+    Ok(fs::canonicalize(folder)?)
+}
+
+fn scan_or_load_snapshot(
+    path: &Path,
+    opts: &Opt,
+    scan_filter: &ScanFilter,
+) -> Result<FileTree, failure::Error> {
+    if path.is_dir() {
+        Ok(scan_folder(
+            scan_root(path.to_path_buf())?,
+            opts.apparent_size,
+            opts.count_links,
+            scan_filter,
+        ))
+    } else {
+        load_snapshot(path)
+    }
+}
+
 fn try_main() -> Result<(), failure::Error> {
     let opts = Opt::from_args();
+    let scan_filter = get_scan_filter(&opts)?;
+    let protected_paths = get_protected_paths(&opts)?;
+
+    let file_tree = match (&opts.load_snapshot, &opts.diff[..]) {
+        (Some(snapshot_path), _) => Some(load_snapshot(snapshot_path)?),
+        (None, [old, new]) => {
+            let old = scan_or_load_snapshot(old, &opts, &scan_filter)?;
+            let new = scan_or_load_snapshot(new, &opts, &scan_filter)?;
+            Some(diff_file_trees(&old, &new))
+        }
+        (None, _) => None,
+    };
+
+    if let Some(format) = opts.report {
+        let file_tree = match file_tree {
+            Some(file_tree) => file_tree,
+            None => {
+                let folder = match opts.folder {
+                    Some(folder) => scan_root(folder)?,
+                    None => env::current_dir()?,
+                };
+                scan_folder(folder, opts.apparent_size, opts.count_links, &scan_filter)
+            }
+        };
+        if let Some(snapshot_path) = &opts.save_snapshot {
+            if let Err(e) = file_tree.save_snapshot(snapshot_path) {
+                failure::bail!("Failed to save snapshot: {}", e)
+            }
+        }
+        let report_options = ReportOptions {
+            format,
+            depth: opts.depth,
+            top: opts.top,
+        };
+        let stdout = io::stdout();
+        let mut writer = io::BufWriter::new(stdout.lock());
+        write_report(&mut writer, &file_tree, &report_options)?;
+        writer.flush()?;
+        if file_tree.failed_to_read > 0 {
+            process::exit(EXIT_CODE_PARTIAL_REPORT);
+        }
+        return Ok(());
+    }
+
+    let dry_run_script = match &opts.dry_run {
+        Some(script_path) => match DryRunScript::open(script_path) {
+            Ok(dry_run_script) => Some(dry_run_script),
+            Err(e) => failure::bail!(
+                "Failed to open dry run script '{}': {}",
+                script_path.to_string_lossy(),
+                e
+            ),
+        },
+        None => None,
+    };
+
+    let audit_log = match &opts.audit_log {
+        Some(log_path) => match AuditLog::open(log_path) {
+            Ok(audit_log) => Some(audit_log),
+            Err(e) => failure::bail!(
+                "Failed to open audit log '{}': {}",
+                log_path.to_string_lossy(),
+                e
+            ),
+        },
+        None => None,
+    };
 
     match get_stdout() {
         Ok(stdout) => {
             enable_raw_mode()?;
             let terminal_backend = CrosstermBackend::new(stdout);
             let terminal_events = TerminalEvents {};
-            let folder = match opts.folder {
-                Some(folder) => folder,
-                None => env::current_dir()?,
+            let folder = match (&file_tree, opts.folder) {
+                (Some(file_tree), _) => file_tree.path_in_filesystem.clone(),
+                (None, Some(folder)) => scan_root(folder)?,
+                (None, None) => env::current_dir()?,
             };
-            if !folder.as_path().is_dir() {
-                failure::bail!("Folder '{}' does not exist", folder.to_string_lossy())
-            }
             start(
                 terminal_backend,
                 Box::new(terminal_events),
                 folder,
-                opts.apparent_size,
-                opts.disable_delete_confirmation,
+                StartOptions {
+                    show_apparent_size: opts.apparent_size,
+                    disable_delete_confirmation: opts.disable_delete_confirmation,
+                    move_to_trash: opts.trash,
+                    dry_run_script,
+                    audit_log,
+                    protected_paths,
+                    read_only: opts.read_only,
+                    save_snapshot: opts.save_snapshot,
+                    open_path: opts.open,
+                    stale_after: opts.stale_after,
+                    count_hard_links: opts.count_links,
+                    scan_filter,
+                    file_tree,
+                },
             );
         }
         Err(_) => failure::bail!("Failed to get stdout: are you trying to pipe 'diskonaut'?"),
@@ -103,12 +390,13 @@ pub fn start<B>(
     terminal_backend: B,
     terminal_events: Box<dyn Iterator<Item = BackEvent> + Send>,
     path: PathBuf,
-    show_apparent_size: bool,
-    disable_delete_confirmation: bool,
+    mut options: StartOptions,
 ) where
     B: Backend + Send + 'static,
 {
     let mut active_threads = vec![];
+    let file_tree = options.file_tree.take();
+    let should_scan = file_tree.is_none();
 
     let (event_sender, event_receiver): (SyncSender<Event>, Receiver<Event>) =
         mpsc::sync_channel(1);
@@ -119,6 +407,11 @@ pub fn start<B>(
 
     let running = Arc::new(AtomicBool::new(true));
     let loaded = Arc::new(AtomicBool::new(false));
+    let watcher = if should_scan && SHOULD_WATCH_FOLDERS {
+        Watcher::new()
+    } else {
+        None
+    };
 
     active_threads.push(
         thread::Builder::new()
@@ -139,10 +432,8 @@ pub fn start<B>(
                 move || {
                     for evt in terminal_events {
                         if let BackEvent::Resize(_x, _y) = evt {
-                            if SHOULD_HANDLE_WIN_CHANGE {
-                                let _ = instruction_sender.send(Instruction::ResetUiMode);
-                                let _ = instruction_sender.send(Instruction::Render);
-                            }
+                            let _ = instruction_sender.send(Instruction::ResetUiMode);
+                            let _ = instruction_sender.send(Instruction::Render);
                             continue;
                         }
 
@@ -179,59 +470,94 @@ pub fn start<B>(
             .unwrap(),
     );
 
-    active_threads.push(
-        thread::Builder::new()
-            .name("hd_scanner".to_string())
-            .spawn({
-                let path = path.clone();
-                let instruction_sender = instruction_sender.clone();
-                let loaded = loaded.clone();
-                move || {
-                    'scanning: for entry in WalkDir::new(&path)
-                        .parallelism(if SHOULD_SCAN_HD_FILES_IN_MULTIPLE_THREADS {
-                            RayonDefaultPool
-                        } else {
-                            Serial
-                        })
-                        .skip_hidden(false)
-                        .follow_links(false)
-                        .into_iter()
-                    {
-                        let instruction_sent = match entry {
-                            Ok(entry) => match entry.metadata() {
-                                Ok(file_metadata) => {
-                                    let entry_path = entry.path();
-                                    instruction_sender.send(Instruction::AddEntryToBaseFolder((
-                                        file_metadata,
-                                        entry_path,
-                                    )))
+    if should_scan {
+        active_threads.push(
+            thread::Builder::new()
+                .name("hd_scanner".to_string())
+                .spawn({
+                    let path = path.clone();
+                    let scan_filter = options.scan_filter.clone();
+                    let instruction_sender = instruction_sender.clone();
+                    let loaded = loaded.clone();
+                    let watcher = watcher.clone();
+                    move || {
+                        if let Some(watcher) = &watcher {
+                            watcher.watch_folder(&path);
+                        }
+                        'scanning: for entry in walk_folder(&path, &scan_filter) {
+                            if let Ok(entry) = &entry {
+                                let instruction = match scan_state(entry) {
+                                    ScanState::Excluded => Some(Instruction::IncrementExcluded(
+                                        entry.parent_path.to_path_buf(),
+                                    )),
+                                    ScanState::MountPoint => {
+                                        Some(Instruction::AddMountPointToBaseFolder(entry.path()))
+                                    }
+                                    ScanState::Scanned => None,
+                                };
+                                if let Some(instruction) = instruction {
+                                    if instruction_sender.send(instruction).is_err() {
+                                        break 'scanning;
+                                    }
+                                    continue;
                                 }
-                                Err(_) => {
-                                    instruction_sender.send(Instruction::IncrementFailedToRead)
+                                if entry.read_children_error.is_some() {
+                                    // the folder itself was read, but we could not list its contents
+                                    let _ = instruction_sender
+                                        .send(Instruction::IncrementFailedToRead(entry.path()));
                                 }
-                            },
-                            Err(_) => instruction_sender.send(Instruction::IncrementFailedToRead),
-                        };
-                        if instruction_sent.is_err() {
-                            // if we fail to send an instruction here, this likely means the program has
-                            // ended and we need to break this loop as well in order not to hang
-                            break 'scanning;
-                        };
+                            }
+                            let instruction_sent = match entry {
+                                Ok(entry) => match entry.metadata() {
+                                    Ok(file_metadata) => {
+                                        let entry_path = entry.path();
+                                        if let Some(watcher) = &watcher {
+                                            if file_metadata.is_dir() {
+                                                watcher.watch_folder(&entry_path);
+                                            }
+                                        }
+                                        instruction_sender.send(Instruction::AddEntryToBaseFolder(
+                                            (file_metadata, entry_path),
+                                        ))
+                                    }
+                                    Err(_) => {
+                                        instruction_sender.send(Instruction::IncrementFailedToRead(
+                                            entry.parent_path.to_path_buf(),
+                                        ))
+                                    }
+                                },
+                                Err(error) => {
+                                    let error_path = error
+                                        .path()
+                                        .map_or_else(|| path.clone(), Path::to_path_buf);
+                                    instruction_sender
+                                        .send(Instruction::IncrementFailedToRead(error_path))
+                                }
+                            };
+                            if instruction_sent.is_err() {
+                                // if we fail to send an instruction here, this likely means the program has
+                                // ended and we need to break this loop as well in order not to hang
+                                break 'scanning;
+                            };
+                        }
+                        let _ = instruction_sender.send(Instruction::StartUi);
+                        loaded.store(true, Ordering::Release);
                     }
-                    let _ = instruction_sender.send(Instruction::StartUi);
-                    loaded.store(true, Ordering::Release);
-                }
-            })
-            .unwrap(),
-    );
+                })
+                .unwrap(),
+        );
+    } else {
+        let _ = instruction_sender.send(Instruction::StartUi);
+    }
 
-    if SHOULD_SHOW_LOADING_ANIMATION {
+    if should_scan && SHOULD_SHOW_LOADING_ANIMATION {
         active_threads.push(
             thread::Builder::new()
                 .name("loading_loop".to_string())
                 .spawn({
                     let instruction_sender = instruction_sender.clone();
                     let running = running.clone();
+                    let loaded = loaded.clone();
                     move || {
                         while running.load(Ordering::Acquire) && !loaded.load(Ordering::Acquire) {
                             let _ =
@@ -245,12 +571,43 @@ pub fn start<B>(
         );
     }
 
+    let watcher_for_rescans = watcher.clone();
+    if let Some(watcher) = watcher {
+        active_threads.push(
+            thread::Builder::new()
+                .name("folder_watcher".to_string())
+                .spawn({
+                    let path = path.clone();
+                    let scan_filter = options.scan_filter.clone();
+                    let instruction_sender = instruction_sender.clone();
+                    let running = running.clone();
+                    move || {
+                        watch_folders(
+                            watcher,
+                            path,
+                            scan_filter,
+                            instruction_sender,
+                            running,
+                            loaded,
+                        )
+                    }
+                })
+                .unwrap(),
+        );
+    }
+
+    let file_tree = file_tree.unwrap_or_else(|| {
+        let mut file_tree = FileTree::new(Folder::new(&path), path, options.show_apparent_size);
+        file_tree.count_hard_links = options.count_hard_links;
+        file_tree
+    });
     let mut app = App::new(
         terminal_backend,
-        path,
+        file_tree,
         event_sender,
-        show_apparent_size,
-        disable_delete_confirmation,
+        instruction_sender,
+        watcher_for_rescans,
+        options,
     );
     app.start(instruction_receiver);
     running.store(false, Ordering::Release);
@@ -5,11 +5,14 @@ use ::std::sync::mpsc::Receiver;
 use ::tui::backend::Backend;
 use crossterm::event::Event as BackEvent;
 
+use crate::deleter::{DeletionProgress, Removal};
 use crate::input::{
-    handle_keypress_delete_file_mode, handle_keypress_error_message, handle_keypress_exiting_mode,
-    handle_keypress_loading_mode, handle_keypress_normal_mode, handle_keypress_screen_too_small,
-    handle_keypress_warning_message,
+    handle_keypress_delete_file_mode, handle_keypress_delete_marked_files_mode,
+    handle_keypress_error_message, handle_keypress_exiting_mode, handle_keypress_help,
+    handle_keypress_loading_mode, handle_keypress_normal_mode, handle_keypress_path_prompt_mode,
+    handle_keypress_screen_too_small, handle_keypress_search_mode, handle_keypress_warning_message,
 };
+use crate::state::files::FileTree;
 use crate::{App, UiMode};
 
 pub enum Instruction {
@@ -18,13 +21,24 @@ pub enum Instruction {
     FlashSpaceFreed,
     UnflashSpaceFreed,
     AddEntryToBaseFolder((Metadata, PathBuf)),
+    AddMountPointToBaseFolder(PathBuf),
+    UpdateEntryInBaseFolder((Metadata, PathBuf)),
+    RemoveEntryFromBaseFolder(PathBuf),
+    ReplaceFolderInBaseFolder(Box<FileTree>),
+    RescanBaseFolder,
     StartUi,
     ToggleScanningVisualIndicator,
     RenderAndUpdateBoard,
     Render,
     ResetUiMode,
     Keypress(BackEvent),
-    IncrementFailedToRead,
+    // the number of changes to the search when it was made
+    FindSearchResults(u64),
+    // the folder the entry we failed to read (or left out) is in
+    IncrementFailedToRead(PathBuf),
+    IncrementExcluded(PathBuf),
+    UpdateDeletionProgress(DeletionProgress),
+    FinishDeletion(Vec<Removal>),
 }
 
 pub fn handle_instructions<B>(app: &mut App<B>, receiver: Receiver<Instruction>)
@@ -51,6 +65,21 @@ where
             Instruction::AddEntryToBaseFolder((file_metadata, entry)) => {
                 app.add_entry_to_base_folder(&file_metadata, entry);
             }
+            Instruction::AddMountPointToBaseFolder(entry) => {
+                app.add_mount_point_to_base_folder(entry);
+            }
+            Instruction::UpdateEntryInBaseFolder((file_metadata, entry)) => {
+                app.update_entry_in_base_folder(&file_metadata, entry);
+            }
+            Instruction::RemoveEntryFromBaseFolder(entry) => {
+                app.remove_entry_from_base_folder(entry);
+            }
+            Instruction::ReplaceFolderInBaseFolder(file_tree) => {
+                app.replace_folder_in_base_folder(*file_tree);
+            }
+            Instruction::RescanBaseFolder => {
+                app.rescan_base_folder();
+            }
             Instruction::StartUi => {
                 app.start_ui();
             }
@@ -81,9 +110,22 @@ where
                         let file_to_delete = file_to_delete.clone();
                         handle_keypress_delete_file_mode(evt, app, file_to_delete);
                     }
+                    UiMode::DeleteMarkedFiles(files_to_delete) => {
+                        let files_to_delete = files_to_delete.clone();
+                        handle_keypress_delete_marked_files_mode(evt, app, files_to_delete);
+                    }
                     UiMode::ErrorMessage(_) => {
                         handle_keypress_error_message(evt, app);
                     }
+                    UiMode::Search(_) => {
+                        handle_keypress_search_mode(evt, app);
+                    }
+                    UiMode::PathPrompt(_) => {
+                        handle_keypress_path_prompt_mode(evt, app);
+                    }
+                    UiMode::Help => {
+                        handle_keypress_help(evt, app);
+                    }
                     UiMode::Exiting { app_loaded: _ } => {
                         handle_keypress_exiting_mode(evt, app);
                     }
@@ -95,8 +137,24 @@ where
                     break;
                 }
             }
-            Instruction::IncrementFailedToRead => {
-                app.increment_failed_to_read();
+            Instruction::FindSearchResults(changes) => {
+                app.find_search_results(changes);
+            }
+            Instruction::IncrementFailedToRead(path) => {
+                app.increment_failed_to_read(&path);
+            }
+            Instruction::IncrementExcluded(path) => {
+                app.increment_excluded(&path);
+            }
+            Instruction::UpdateDeletionProgress(progress) => {
+                app.update_deletion_progress(progress);
+            }
+            Instruction::FinishDeletion(removals) => {
+                app.finish_background_deletion(removals);
+                // we might have been purging the trash before exiting
+                if !app.is_running {
+                    break;
+                }
             }
         }
     }
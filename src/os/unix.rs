@@ -1,5 +1,48 @@
-use nix::unistd::geteuid;
+use ::std::ffi::{OsStr, OsString};
+use ::std::fs::Metadata;
+use ::std::os::unix::ffi::{OsStrExt, OsStringExt};
+use ::std::os::unix::fs::MetadataExt;
+
+use nix::unistd::{geteuid, Gid, Group, Uid, User};
 
 pub(crate) fn is_user_admin() -> bool {
     geteuid().is_root()
 }
+
+pub(crate) fn os_str_to_bytes(os_str: &OsStr) -> Vec<u8> {
+    os_str.as_bytes().to_vec()
+}
+
+pub(crate) fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from_vec(bytes)
+}
+
+pub(crate) fn get_device_id(metadata: &Metadata) -> Option<u64> {
+    Some(metadata.dev())
+}
+
+pub(crate) fn get_hard_link_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    if metadata.nlink() > 1 {
+        Some((metadata.dev(), metadata.ino()))
+    } else {
+        None
+    }
+}
+
+pub(crate) fn get_owner_ids(metadata: &Metadata) -> Option<(u32, u32)> {
+    Some((metadata.uid(), metadata.gid()))
+}
+
+pub(crate) fn user_name(uid: u32) -> Option<String> {
+    User::from_uid(Uid::from_raw(uid))
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+}
+
+pub(crate) fn group_name(gid: u32) -> Option<String> {
+    Group::from_gid(Gid::from_raw(gid))
+        .ok()
+        .flatten()
+        .map(|group| group.name)
+}
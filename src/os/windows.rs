@@ -1,3 +1,7 @@
+use ::std::ffi::{OsStr, OsString};
+use ::std::fs::Metadata;
+use ::std::os::windows::ffi::{OsStrExt, OsStringExt};
+
 #[cfg(not(test))]
 use winapi::um::winnt::{
     DOMAIN_ALIAS_RID_ADMINS, PVOID, SECURITY_BUILTIN_DOMAIN_RID, SECURITY_NT_AUTHORITY,
@@ -39,3 +43,43 @@ pub(crate) fn is_user_admin() -> bool {
 pub(crate) fn is_user_admin() -> bool {
     false
 }
+
+pub(crate) fn os_str_to_bytes(os_str: &OsStr) -> Vec<u8> {
+    os_str
+        .encode_wide()
+        .flat_map(|wide_char| wide_char.to_le_bytes().to_vec())
+        .collect()
+}
+
+pub(crate) fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    let wide_chars: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| u16::from_le_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect();
+    OsString::from_wide(&wide_chars)
+}
+
+pub(crate) fn get_device_id(_metadata: &Metadata) -> Option<u64> {
+    // the volume serial number is not available on stable, so we cannot
+    // tell when a folder is on a different volume
+    None
+}
+
+pub(crate) fn get_hard_link_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    // the file index is not available on stable, so hard links are counted
+    // once for every link
+    None
+}
+
+pub(crate) fn get_owner_ids(_metadata: &Metadata) -> Option<(u32, u32)> {
+    // files are owned by security identifiers rather than user and group ids
+    None
+}
+
+pub(crate) fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+pub(crate) fn group_name(_gid: u32) -> Option<String> {
+    None
+}
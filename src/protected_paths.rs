@@ -0,0 +1,290 @@
+use ::std::collections::{BTreeMap, HashSet};
+use ::std::env;
+use ::std::ffi::OsString;
+use ::std::fs;
+use ::std::ops::Bound;
+use ::std::path::{Path, PathBuf};
+
+use ::globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::state::files::{FileOrFolder, Folder};
+
+// protected unless told otherwise (see --no-default-protect), along with the user's home folder
+const DEFAULT_PROTECTED: [&str; 3] = ["/", "/usr", ".git"];
+
+// files and folders we refuse to delete
+#[derive(Clone)]
+pub struct ProtectedPaths {
+    globs: GlobSet,
+    // the patterns the globs were built from, to tell the user which one matched
+    patterns: Vec<String>,
+    paths: Vec<PathBuf>,
+    // the files and folders matching the globs that we scanned, kept up to date as
+    // they are added and removed so that we never have to look through everything
+    // in a folder to find them (the ones in a folder come right after it)
+    found: BTreeMap<PathBuf, String>,
+}
+
+impl Default for ProtectedPaths {
+    fn default() -> Self {
+        ProtectedPaths {
+            globs: GlobSet::empty(),
+            patterns: vec![],
+            paths: vec![],
+            found: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProtectedPaths {
+    pub fn new(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(Glob::new(pattern)?);
+        }
+        Ok(ProtectedPaths {
+            globs: builder.build()?,
+            patterns: patterns.to_vec(),
+            paths: vec![],
+            found: BTreeMap::new(),
+        })
+    }
+    pub fn with_defaults(patterns: &[String]) -> Result<Self, globset::Error> {
+        let mut all_patterns: Vec<String> =
+            DEFAULT_PROTECTED.iter().map(|p| p.to_string()).collect();
+        all_patterns.extend_from_slice(patterns);
+        let mut protected_paths = ProtectedPaths::new(&all_patterns)?;
+        // added as a path rather than a glob, since it might contain glob characters
+        if let Some(home) = env::var_os("HOME") {
+            protected_paths.paths.push(canonical_path(Path::new(&home)));
+        }
+        Ok(protected_paths)
+    }
+    fn matching_glob(&self, canonical_path: &Path) -> Option<String> {
+        let mut matches = self.globs.matches(canonical_path);
+        if let Some(name) = canonical_path.file_name() {
+            matches.extend(self.globs.matches(name));
+        }
+        matches
+            .into_iter()
+            .min()
+            .map(|index| self.patterns[index].clone())
+    }
+    fn matching_rule(&self, canonical_path: &Path) -> Option<String> {
+        if let Some(path) = self.paths.iter().find(|path| *path == canonical_path) {
+            return Some(path.to_string_lossy().into_owned());
+        }
+        if let Some(pattern) = self.matching_glob(canonical_path) {
+            return Some(pattern);
+        }
+        // deleting a folder would also delete any protected path inside it
+        if let Some(path) = self
+            .paths
+            .iter()
+            .find(|path| path.starts_with(canonical_path))
+        {
+            return Some(path.to_string_lossy().into_owned());
+        }
+        self.patterns
+            .iter()
+            .find(|pattern| {
+                is_literal_path(pattern) && Path::new(pattern).starts_with(canonical_path)
+            })
+            .cloned()
+    }
+    pub fn protected_by(&self, path: &Path) -> Option<String> {
+        // returns the path or glob that protects this path, if any
+        if self.paths.is_empty() && self.globs.is_empty() {
+            return None;
+        }
+        self.matching_rule(&canonical_path(path))
+    }
+    pub fn add_scanned(&mut self, path: &Path) {
+        // the paths we scan are already canonical, since the folder we scan is
+        if self.globs.is_empty() {
+            return;
+        }
+        if let Some(pattern) = self.matching_glob(path) {
+            self.found.insert(path.to_path_buf(), pattern);
+        }
+    }
+    pub fn add_scanned_folder(&mut self, path: &Path, folder: &Folder) {
+        if self.globs.is_empty() {
+            return;
+        }
+        for (name, scanned) in &folder.contents {
+            let path = path.join(name);
+            self.add_scanned(&path);
+            if let FileOrFolder::Folder(folder) = scanned {
+                self.add_scanned_folder(&path, folder);
+            }
+        }
+    }
+    pub fn remove_scanned(&mut self, path: &Path) {
+        // along with everything in it
+        let removed: Vec<PathBuf> = self
+            .found
+            .range::<Path, _>((Bound::Included(path), Bound::Unbounded))
+            .map(|(found_path, _)| found_path)
+            .take_while(|found_path| found_path.starts_with(path))
+            .cloned()
+            .collect();
+        for found_path in removed {
+            self.found.remove(&found_path);
+        }
+    }
+    fn found_within(&self, canonical_path: &Path) -> Option<(&PathBuf, &String)> {
+        self.found
+            .range::<Path, _>((Bound::Excluded(canonical_path), Bound::Unbounded))
+            .next()
+            .filter(|(found_path, _)| found_path.starts_with(canonical_path))
+    }
+    pub fn protected_within(&self, path: &Path) -> Option<(PathBuf, String)> {
+        // returns a protected file or folder we scanned inside this one
+        // (relative to it), along with what protects it
+        if self.found.is_empty() {
+            return None;
+        }
+        let canonical_path = canonical_path(path);
+        let (protected_path, rule) = self.found_within(&canonical_path)?;
+        let relative_path = protected_path
+            .strip_prefix(&canonical_path)
+            .unwrap_or(protected_path);
+        Some((relative_path.to_path_buf(), rule.clone()))
+    }
+    pub fn protected_names(&self, folder: &Path, names: Vec<&OsString>) -> HashSet<OsString> {
+        if self.paths.is_empty() && self.globs.is_empty() {
+            return HashSet::new();
+        }
+        let folder = canonical_path(folder);
+        names
+            .into_iter()
+            .filter(|name| {
+                let path = folder.join(name);
+                self.matching_rule(&path).is_some() || self.found_within(&path).is_some()
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn is_literal_path(pattern: &str) -> bool {
+    // a glob without any wildcards, which only ever matches the one path
+    Path::new(pattern).is_absolute() && !pattern.contains(['*', '?', '[', '{'].as_ref())
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    // we resolve the folders leading to the path but not the path itself,
+    // since deleting a symlink does not touch what it points to
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if parent != Path::new("") => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::files::Folder;
+
+    #[test]
+    fn match_protected_paths_and_globs() {
+        let protected_paths = ProtectedPaths::new(&[
+            String::from("/"),
+            String::from("/usr"),
+            String::from(".git"),
+            String::from("/tmp/**/*.keep"),
+        ])
+        .expect("failed to create protected paths");
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/")),
+            Some(String::from("/"))
+        );
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/usr")),
+            Some(String::from("/usr"))
+        );
+        assert_eq!(protected_paths.protected_by(Path::new("/usr/lib")), None);
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/project/.git")),
+            Some(String::from(".git"))
+        );
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/project/data.keep")),
+            Some(String::from("/tmp/**/*.keep"))
+        );
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/project/data")),
+            None
+        );
+    }
+
+    #[test]
+    fn protect_folders_containing_protected_paths() {
+        let protected_paths = ProtectedPaths::new(&[
+            String::from("/tmp/project/data"),
+            String::from("/tmp/**/*.keep"),
+        ])
+        .expect("failed to create protected paths");
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/project")),
+            Some(String::from("/tmp/project/data"))
+        );
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp")),
+            Some(String::from("/tmp/project/data"))
+        );
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/project/other")),
+            None
+        );
+        // a glob with wildcards matches nothing in particular
+        assert_eq!(
+            protected_paths.protected_by(Path::new("/tmp/other_project")),
+            None
+        );
+    }
+
+    #[test]
+    fn protect_folders_containing_protected_names() {
+        let mut protected_paths =
+            ProtectedPaths::new(&[String::from(".git")]).expect("failed to create protected paths");
+        let mut folder = Folder::new(&PathBuf::from("/tmp/projects"));
+        folder.add_file(PathBuf::from("project/.git/config"), 4096);
+        folder.add_file(PathBuf::from("project/src/main.rs"), 4096);
+        folder.add_file(PathBuf::from("notes/todo"), 4096);
+        protected_paths.add_scanned_folder(Path::new("/tmp/projects"), &folder);
+
+        assert_eq!(
+            protected_paths.protected_within(Path::new("/tmp/projects")),
+            Some((PathBuf::from("project/.git"), String::from(".git")))
+        );
+        assert_eq!(
+            protected_paths.protected_within(Path::new("/tmp/projects/project")),
+            Some((PathBuf::from(".git"), String::from(".git")))
+        );
+        assert_eq!(
+            protected_paths.protected_within(Path::new("/tmp/projects/notes")),
+            None
+        );
+        let project = OsString::from("project");
+        let notes = OsString::from("notes");
+        assert_eq!(
+            protected_paths.protected_names(Path::new("/tmp/projects"), vec![&project, &notes]),
+            vec![project.clone()].into_iter().collect()
+        );
+
+        protected_paths.remove_scanned(Path::new("/tmp/projects/project/.git"));
+        assert_eq!(
+            protected_paths.protected_within(Path::new("/tmp/projects/project")),
+            None
+        );
+        protected_paths.add_scanned(Path::new("/tmp/projects/notes/.git"));
+        assert_eq!(
+            protected_paths.protected_within(Path::new("/tmp/projects")),
+            Some((PathBuf::from("notes/.git"), String::from(".git")))
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use ::std::io::{self, Write};
+use ::std::path::{Path, PathBuf};
+use ::std::str::FromStr;
+
+use crate::state::files::{FileOrFolder, FileTree, Folder};
+use crate::state::tiles::{files_in_folder, FileMetadata, FileType, View};
+use crate::ui::format::DisplaySize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Text,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "text" => Ok(ReportFormat::Text),
+            _ => Err(format!("unknown report format: {}", format)),
+        }
+    }
+}
+
+pub struct ReportOptions {
+    pub format: ReportFormat,
+    // how many levels below the base folder to include (0 is only the base folder)
+    pub depth: Option<usize>,
+    // how many of the largest entries to include in every folder
+    pub top: Option<usize>,
+}
+
+// a single line of the report, these are listed depth first in the
+// same order the ui shows them (largest first)
+struct ReportEntry {
+    path: PathBuf,
+    depth: usize,
+    file_type: FileType,
+    size: u128,
+    descendants: Option<u64>,
+}
+
+fn reached_max_depth(depth: usize, options: &ReportOptions) -> bool {
+    match options.depth {
+        Some(max_depth) => depth >= max_depth,
+        None => false,
+    }
+}
+
+fn contents_to_report<'a>(
+    folder: &'a Folder,
+    options: &ReportOptions,
+) -> Vec<(FileMetadata, &'a FileOrFolder)> {
+    let mut files = files_in_folder(folder, 0, None, None, &View::Folder);
+    if let Some(top) = options.top {
+        files.truncate(top);
+    }
+    files
+        .into_iter()
+        .map(|file_metadata| {
+            let file_or_folder = &folder.contents[&file_metadata.name];
+            (file_metadata, file_or_folder)
+        })
+        .collect()
+}
+
+fn collect_entries(
+    folder: &Folder,
+    path: &Path,
+    depth: usize,
+    options: &ReportOptions,
+    entries: &mut Vec<ReportEntry>,
+) {
+    if reached_max_depth(depth, options) {
+        return;
+    }
+    for (file_metadata, file_or_folder) in contents_to_report(folder, options) {
+        let entry_path = path.join(&file_metadata.name);
+        entries.push(ReportEntry {
+            path: entry_path.clone(),
+            depth: depth + 1,
+            file_type: file_metadata.file_type,
+            size: file_metadata.size,
+            descendants: file_metadata.descendants,
+        });
+        if let FileOrFolder::Folder(folder) = file_or_folder {
+            collect_entries(folder, &entry_path, depth + 1, options, entries);
+        }
+    }
+}
+
+pub fn file_type_name(file_type: FileType) -> &'static str {
+    match file_type {
+        FileType::File => "file",
+        FileType::Folder => "folder",
+    }
+}
+
+pub fn escape_json(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_csv(string: &str) -> String {
+    if string.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", string.replace('"', "\"\""))
+    } else {
+        string.to_owned()
+    }
+}
+
+fn write_json_folder(
+    writer: &mut impl Write,
+    folder: &Folder,
+    path: &Path,
+    depth: usize,
+    options: &ReportOptions,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "{{\"name\":\"{}\",\"path\":\"{}\",\"type\":\"folder\",\"size\":{},\"descendants\":{}",
+        escape_json(&folder.name.to_string_lossy()),
+        escape_json(&path.to_string_lossy()),
+        folder.size,
+        folder.num_descendants
+    )?;
+    if reached_max_depth(depth, options) {
+        return write!(writer, "}}");
+    }
+    write!(writer, ",\"contents\":[")?;
+    for (index, (file_metadata, file_or_folder)) in
+        contents_to_report(folder, options).into_iter().enumerate()
+    {
+        if index > 0 {
+            write!(writer, ",")?;
+        }
+        let entry_path = path.join(&file_metadata.name);
+        match file_or_folder {
+            FileOrFolder::Folder(folder) => {
+                write_json_folder(writer, folder, &entry_path, depth + 1, options)?
+            }
+            FileOrFolder::File(file) => write!(
+                writer,
+                "{{\"name\":\"{}\",\"path\":\"{}\",\"type\":\"file\",\"size\":{}}}",
+                escape_json(&file.name.to_string_lossy()),
+                escape_json(&entry_path.to_string_lossy()),
+                file.size
+            )?,
+        }
+    }
+    write!(writer, "]}}")
+}
+
+fn write_json(
+    writer: &mut impl Write,
+    file_tree: &FileTree,
+    options: &ReportOptions,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "{{\"apparent_size\":{},\"failed_to_read\":{},\"excluded\":{},\"root\":",
+        file_tree.show_apparent_size, file_tree.failed_to_read, file_tree.excluded
+    )?;
+    write_json_folder(
+        writer,
+        file_tree.get_base_folder(),
+        &file_tree.path_in_filesystem,
+        0,
+        options,
+    )?;
+    writeln!(writer, "}}")
+}
+
+fn write_csv(
+    writer: &mut impl Write,
+    file_tree: &FileTree,
+    entries: &[ReportEntry],
+) -> io::Result<()> {
+    writeln!(writer, "path,type,size,descendants")?;
+    writeln!(
+        writer,
+        "{},folder,{},{}",
+        escape_csv(&file_tree.path_in_filesystem.to_string_lossy()),
+        file_tree.get_total_size(),
+        file_tree.get_total_descendants()
+    )?;
+    for entry in entries {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            escape_csv(&entry.path.to_string_lossy()),
+            file_type_name(entry.file_type),
+            entry.size,
+            entry
+                .descendants
+                .map(|descendants| descendants.to_string())
+                .unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_text(
+    writer: &mut impl Write,
+    file_tree: &FileTree,
+    entries: &[ReportEntry],
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:>8}  {} ({} files)",
+        DisplaySize(file_tree.get_total_size() as f64).to_string(),
+        file_tree.path_in_filesystem.to_string_lossy(),
+        file_tree.get_total_descendants()
+    )?;
+    for entry in entries {
+        let name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let suffix = match entry.file_type {
+            FileType::Folder => "/",
+            FileType::File => "",
+        };
+        writeln!(
+            writer,
+            "{:>8}  {}{}{}",
+            DisplaySize(entry.size as f64).to_string(),
+            "  ".repeat(entry.depth),
+            name,
+            suffix
+        )?;
+    }
+    if file_tree.failed_to_read > 0 {
+        writeln!(
+            writer,
+            "(failed to read {} files)",
+            file_tree.failed_to_read
+        )?;
+    }
+    if file_tree.excluded > 0 {
+        writeln!(writer, "(excluded {} files)", file_tree.excluded)?;
+    }
+    Ok(())
+}
+
+pub fn write_report(
+    writer: &mut impl Write,
+    file_tree: &FileTree,
+    options: &ReportOptions,
+) -> io::Result<()> {
+    if options.format == ReportFormat::Json {
+        return write_json(writer, file_tree, options);
+    }
+    let mut entries = vec![];
+    collect_entries(
+        file_tree.get_base_folder(),
+        &file_tree.path_in_filesystem,
+        0,
+        options,
+        &mut entries,
+    );
+    match options.format {
+        ReportFormat::Csv => write_csv(writer, file_tree, &entries),
+        _ => write_text(writer, file_tree, &entries),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(format: ReportFormat, depth: Option<usize>, top: Option<usize>) -> String {
+        let path = PathBuf::from("/tmp/base");
+        let mut base_folder = Folder::new(&path);
+        base_folder.add_file(PathBuf::from("subfolder/file1"), 4096);
+        base_folder.add_file(PathBuf::from("subfolder/file,2"), 1024);
+        base_folder.add_file(PathBuf::from("file3"), 2048);
+        let file_tree = FileTree::new(base_folder, path, true);
+        let mut output = vec![];
+        write_report(
+            &mut output,
+            &file_tree,
+            &ReportOptions { format, depth, top },
+        )
+        .expect("failed to write report");
+        String::from_utf8(output).expect("report is not utf8")
+    }
+
+    #[test]
+    fn csv_report() {
+        assert_eq!(
+            report(ReportFormat::Csv, None, None),
+            "path,type,size,descendants\n\
+             /tmp/base,folder,7168,3\n\
+             /tmp/base/subfolder,folder,5120,2\n\
+             /tmp/base/subfolder/file1,file,4096,\n\
+             \"/tmp/base/subfolder/file,2\",file,1024,\n\
+             /tmp/base/file3,file,2048,\n"
+        );
+    }
+
+    #[test]
+    fn text_report_with_depth_and_top() {
+        assert_eq!(
+            report(ReportFormat::Text, Some(1), Some(1)),
+            "    7.0K  /tmp/base (3 files)\n\
+             \x20   5.0K    subfolder/\n"
+        );
+    }
+
+    #[test]
+    fn json_report_with_depth() {
+        assert_eq!(
+            report(ReportFormat::Json, Some(1), None),
+            "{\"apparent_size\":true,\"failed_to_read\":0,\"excluded\":0,\"root\":\
+             {\"name\":\"base\",\"path\":\"/tmp/base\",\"type\":\"folder\",\"size\":7168,\"descendants\":3,\"contents\":[\
+             {\"name\":\"subfolder\",\"path\":\"/tmp/base/subfolder\",\"type\":\"folder\",\"size\":5120,\"descendants\":2},\
+             {\"name\":\"file3\",\"path\":\"/tmp/base/file3\",\"type\":\"file\",\"size\":2048}]}}\n"
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use ::std::fs::{self, Metadata};
+use ::std::io;
+use ::std::path::{Path, PathBuf};
+use ::std::sync::Arc;
+use ::std::time::Duration;
+
+use ::globset::{Glob, GlobSet, GlobSetBuilder};
+use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ::jwalk::Parallelism::{RayonDefaultPool, Serial};
+use ::jwalk::{DirEntry, WalkDirGeneric};
+
+use crate::age::age_cutoff;
+use crate::state::files::{FileTimes, FileTree, Folder};
+use crate::watcher::Watcher;
+use crate::SHOULD_SCAN_HD_FILES_IN_MULTIPLE_THREADS;
+
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::get_device_id;
+#[cfg(target_os = "windows")]
+use crate::os::windows::get_device_id;
+
+// the .gitignore files of the folders above the one being read, innermost last
+#[derive(Debug, Default, Clone)]
+pub struct ReadDirState {
+    gitignores: Vec<Arc<Gitignore>>,
+}
+
+// entries that were filtered out are still yielded by the walk (so they
+// can be counted or shown) but their contents are never read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanState {
+    Scanned,
+    Excluded,
+    // a folder on a different filesystem than the scanned folder
+    MountPoint,
+}
+
+impl Default for ScanState {
+    fn default() -> Self {
+        ScanState::Scanned
+    }
+}
+
+pub type WalkDir = WalkDirGeneric<(ReadDirState, ScanState)>;
+pub type WalkDirEntry = DirEntry<(ReadDirState, ScanState)>;
+
+#[derive(Clone)]
+pub struct ScanFilter {
+    exclude: GlobSet,
+    respect_gitignore: bool,
+    one_file_system: bool,
+    // only files modified before / after these times (in seconds since
+    // the unix epoch) are counted
+    modified_before: Option<u64>,
+    modified_after: Option<u64>,
+}
+
+impl Default for ScanFilter {
+    fn default() -> Self {
+        ScanFilter {
+            exclude: GlobSet::empty(),
+            respect_gitignore: false,
+            one_file_system: false,
+            modified_before: None,
+            modified_after: None,
+        }
+    }
+}
+
+impl ScanFilter {
+    pub fn new(exclude: &[String], respect_gitignore: bool) -> Result<Self, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in exclude {
+            builder.add(Glob::new(pattern)?);
+        }
+        Ok(ScanFilter {
+            exclude: builder.build()?,
+            respect_gitignore,
+            one_file_system: false,
+            modified_before: None,
+            modified_after: None,
+        })
+    }
+    pub fn one_file_system(mut self, one_file_system: bool) -> Self {
+        self.one_file_system = one_file_system;
+        self
+    }
+    pub fn modified(mut self, older_than: Option<Duration>, newer_than: Option<Duration>) -> Self {
+        self.modified_before = older_than.map(age_cutoff);
+        self.modified_after = newer_than.map(age_cutoff);
+        self
+    }
+    fn is_empty(&self) -> bool {
+        self.exclude.is_empty()
+            && !self.respect_gitignore
+            && !self.one_file_system
+            && !self.filters_by_age()
+    }
+    fn filters_by_age(&self) -> bool {
+        self.modified_before.is_some() || self.modified_after.is_some()
+    }
+    pub fn is_excluded_by_age(&self, metadata: &Metadata) -> bool {
+        // folders are always scanned, only the files in them are left out
+        if !self.filters_by_age() || metadata.is_dir() {
+            return false;
+        }
+        let modified = match FileTimes::from_metadata(metadata) {
+            Some(times) => times.modified,
+            None => return false,
+        };
+        let too_new = matches!(self.modified_before, Some(before) if modified > before);
+        let too_old = matches!(self.modified_after, Some(after) if modified < after);
+        too_new || too_old
+    }
+    pub fn is_excluded_by_glob(&self, path: &Path, root: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let relative_path = path.strip_prefix(root).unwrap_or(path);
+        let is_name_excluded = path
+            .file_name()
+            .map(|name| self.exclude.is_match(name))
+            .unwrap_or(false);
+        is_name_excluded || self.exclude.is_match(relative_path)
+    }
+    fn filter_entries(
+        &self,
+        root: &Path,
+        root_device_id: Option<u64>,
+        read_dir_state: &mut ReadDirState,
+        children: &mut [jwalk::Result<WalkDirEntry>],
+    ) {
+        if self.respect_gitignore {
+            let gitignore_path = children.iter().find_map(|child| match child {
+                Ok(entry) if entry.file_name == ".gitignore" && entry.file_type.is_file() => {
+                    Some(entry.path())
+                }
+                _ => None,
+            });
+            if let Some(gitignore_path) = gitignore_path {
+                let folder = gitignore_path.parent().unwrap_or(root);
+                let mut builder = GitignoreBuilder::new(folder);
+                // a .gitignore we cannot parse just doesn't exclude anything
+                builder.add(&gitignore_path);
+                if let Ok(gitignore) = builder.build() {
+                    read_dir_state.gitignores.push(Arc::new(gitignore));
+                }
+            }
+        }
+        for entry in children.iter_mut().flatten() {
+            let excluded = self.is_excluded_by_glob(&entry.path(), root)
+                || is_gitignored(entry, &read_dir_state.gitignores)
+                || is_excluded_by_age(self, entry);
+            if excluded {
+                entry.client_state = ScanState::Excluded;
+                entry.read_children_path = None;
+            } else if self.one_file_system
+                && entry.file_type.is_dir()
+                && is_on_other_device(entry, root_device_id)
+            {
+                entry.client_state = ScanState::MountPoint;
+                entry.read_children_path = None;
+            }
+        }
+    }
+}
+
+fn is_gitignored(entry: &WalkDirEntry, gitignores: &[Arc<Gitignore>]) -> bool {
+    let path = entry.path();
+    let is_dir = entry.file_type.is_dir();
+    for gitignore in gitignores.iter().rev() {
+        let matched = gitignore.matched(&path, is_dir);
+        if matched.is_ignore() {
+            return true;
+        } else if matched.is_whitelist() {
+            return false;
+        }
+    }
+    false
+}
+
+fn is_excluded_by_age(scan_filter: &ScanFilter, entry: &WalkDirEntry) -> bool {
+    if !scan_filter.filters_by_age() || entry.file_type.is_dir() {
+        return false;
+    }
+    match entry.metadata() {
+        Ok(metadata) => scan_filter.is_excluded_by_age(&metadata),
+        Err(_) => false,
+    }
+}
+
+fn is_on_other_device(entry: &WalkDirEntry, root_device_id: Option<u64>) -> bool {
+    let device_id = entry
+        .metadata()
+        .ok()
+        .and_then(|metadata| get_device_id(&metadata));
+    match (root_device_id, device_id) {
+        (Some(root_device_id), Some(device_id)) => root_device_id != device_id,
+        _ => false,
+    }
+}
+
+pub fn scan_state(entry: &WalkDirEntry) -> ScanState {
+    entry.client_state
+}
+
+pub fn read_exclude_file(path: &Path) -> io::Result<Vec<String>> {
+    // one glob per line, empty lines and lines starting with # are ignored
+    let contents = ::std::fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+pub fn walk_folder(path: &Path, scan_filter: &ScanFilter) -> WalkDir {
+    walk_subfolder(path, path, scan_filter)
+}
+
+pub fn walk_subfolder(path: &Path, root: &Path, scan_filter: &ScanFilter) -> WalkDir {
+    // root is the folder the scan started from, excluded globs are matched relative to it
+    let walk_dir = WalkDir::new(path)
+        .parallelism(if SHOULD_SCAN_HD_FILES_IN_MULTIPLE_THREADS {
+            RayonDefaultPool
+        } else {
+            Serial
+        })
+        .skip_hidden(false)
+        .follow_links(false);
+    if scan_filter.is_empty() {
+        return walk_dir;
+    }
+    let scan_filter = scan_filter.clone();
+    let root = root.to_path_buf();
+    let root_device_id = fs::metadata(&root)
+        .ok()
+        .and_then(|metadata| get_device_id(&metadata));
+    walk_dir.process_read_dir(move |read_dir_state, children| {
+        // the base folder itself is never excluded
+        if children.iter().any(|child| match child {
+            Ok(entry) => entry.depth == 0,
+            Err(_) => false,
+        }) {
+            return;
+        }
+        scan_filter.filter_entries(&root, root_device_id, read_dir_state, children);
+    })
+}
+
+pub fn scan_folder(
+    path: PathBuf,
+    show_apparent_size: bool,
+    count_hard_links: bool,
+    scan_filter: &ScanFilter,
+) -> FileTree {
+    // this scans the whole folder before returning, for when we need
+    // the full file tree without showing the ui while scanning
+    scan_subfolder(
+        path.clone(),
+        &path,
+        show_apparent_size,
+        count_hard_links,
+        scan_filter,
+    )
+}
+
+pub fn scan_subfolder(
+    path: PathBuf,
+    root: &Path,
+    show_apparent_size: bool,
+    count_hard_links: bool,
+    scan_filter: &ScanFilter,
+) -> FileTree {
+    let mut file_tree = FileTree::new(Folder::new(&path), path, show_apparent_size);
+    file_tree.count_hard_links = count_hard_links;
+    scan_into(file_tree, root, scan_filter, None)
+}
+
+pub fn scan_into(
+    mut file_tree: FileTree,
+    root: &Path,
+    scan_filter: &ScanFilter,
+    watcher: Option<&Watcher>,
+) -> FileTree {
+    // the returned file tree starts at its own path, but entries are filtered the way
+    // they would have been when scanning root (.gitignore files in the folders
+    // between the two are not read though)
+    let path = file_tree.path_in_filesystem.clone();
+    for entry in walk_subfolder(&path, root, scan_filter) {
+        if let Ok(entry) = &entry {
+            match scan_state(entry) {
+                ScanState::Excluded => {
+                    file_tree.add_excluded(&entry.parent_path);
+                    continue;
+                }
+                ScanState::MountPoint => {
+                    file_tree.add_mount_point(&entry.path());
+                    continue;
+                }
+                ScanState::Scanned => {}
+            }
+            if entry.read_children_error.is_some() {
+                // the folder itself was read, but we could not list its contents
+                file_tree.add_failed_to_read(&entry.path());
+            }
+        }
+        match entry {
+            Ok(entry) => match entry.metadata() {
+                Ok(file_metadata) => {
+                    let entry_path = entry.path();
+                    if let Some(watcher) = watcher {
+                        if file_metadata.is_dir() {
+                            watcher.watch_folder(&entry_path);
+                        }
+                    }
+                    file_tree.add_entry(&file_metadata, &entry_path)
+                }
+                Err(_) => file_tree.add_failed_to_read(&entry.parent_path),
+            },
+            Err(error) => file_tree.add_failed_to_read(error.path().unwrap_or(&path)),
+        }
+    }
+    file_tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::fs::{create_dir_all, hard_link, remove_dir_all, write};
+
+    #[test]
+    fn exclude_globs_and_gitignore() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/scanner_exclude");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("node_modules/package")).expect("failed to create folder");
+        create_dir_all(path.join("src/target")).expect("failed to create folder");
+        write(path.join("node_modules/package/index.js"), "x").expect("failed to create file");
+        write(path.join("src/main.rs"), "x").expect("failed to create file");
+        write(path.join("src/main.rs.bk"), "x").expect("failed to create file");
+        write(path.join("src/target/binary"), "x").expect("failed to create file");
+        write(path.join("src/.gitignore"), "target/\n").expect("failed to create file");
+
+        let scan_filter =
+            ScanFilter::new(&[String::from("node_modules"), String::from("*.bk")], true)
+                .expect("failed to create scan filter");
+        let file_tree = scan_folder(path.clone(), true, false, &scan_filter);
+        remove_dir_all(&path).ok();
+
+        // node_modules, main.rs.bk and src/target
+        assert_eq!(file_tree.excluded, 3);
+        // src, src/main.rs and src/.gitignore
+        assert_eq!(file_tree.get_total_descendants(), 3);
+    }
+
+    #[test]
+    fn filter_files_by_age() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/scanner_age");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("subfolder")).expect("failed to create folder");
+        write(path.join("file1"), "x").expect("failed to create file");
+        write(path.join("subfolder/file2"), "x").expect("failed to create file");
+
+        let day = Duration::from_secs(24 * 60 * 60);
+        let recent = ScanFilter::default().modified(None, Some(day));
+        let recent_tree = scan_folder(path.clone(), true, false, &recent);
+        let old = ScanFilter::default().modified(Some(day), None);
+        let old_tree = scan_folder(path.clone(), true, false, &old);
+        remove_dir_all(&path).ok();
+
+        // subfolder, file1 and subfolder/file2 were all just written
+        assert_eq!(recent_tree.excluded, 0);
+        assert_eq!(recent_tree.get_total_descendants(), 3);
+        // the folder is still scanned, but both files are left out
+        assert_eq!(old_tree.excluded, 2);
+        assert_eq!(old_tree.get_total_descendants(), 1);
+    }
+
+    #[test]
+    fn rescan_folders_without_counting_twice() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/scanner_rescan");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("subfolder1")).expect("failed to create folder");
+        create_dir_all(path.join("subfolder2")).expect("failed to create folder");
+        write(path.join("subfolder1/file1"), "xxxx").expect("failed to create file");
+        hard_link(
+            path.join("subfolder1/file1"),
+            path.join("subfolder2/file1_link"),
+        )
+        .expect("failed to create hard link");
+        write(path.join("subfolder2/file2"), "x").expect("failed to create file");
+        write(path.join("subfolder2/file2.bk"), "x").expect("failed to create file");
+
+        let scan_filter =
+            ScanFilter::new(&[String::from("*.bk")], false).expect("failed to create scan filter");
+        let mut file_tree = scan_folder(path.clone(), true, false, &scan_filter);
+        let total_size = file_tree.get_total_size();
+        // whichever of the two links was counted, one of these rescans does not find it
+        for subfolder in &["subfolder1", "subfolder2", "subfolder2"] {
+            let rescanned = scan_into(
+                file_tree.subfolder_to_rescan(path.join(subfolder)),
+                &path,
+                &scan_filter,
+                None,
+            );
+            file_tree.replace_folder(rescanned);
+        }
+        remove_dir_all(&path).ok();
+
+        // the hard link is still counted once, and subfolder2/file2.bk is still left out once
+        assert_eq!(file_tree.get_total_size(), total_size);
+        assert_eq!(file_tree.excluded, 1);
+        assert_eq!(file_tree.get_total_descendants(), 5);
+    }
+}
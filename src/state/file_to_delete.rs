@@ -10,6 +10,8 @@ pub struct FileToDelete {
     pub file_type: FileType,
     pub num_descendants: Option<u64>,
     pub size: u128,
+    // move to the trash rather than deleting permanently
+    pub move_to_trash: bool,
 }
 
 impl FileToDelete {
@@ -0,0 +1,178 @@
+use ::std::collections::HashSet;
+use ::std::ffi::OsString;
+
+use crate::state::files::{File, FileOrFolder, FileTree, Folder};
+
+fn size_of(entry: &FileOrFolder, show_apparent_size: bool) -> u128 {
+    if show_apparent_size {
+        entry.apparent_size()
+    } else {
+        entry.disk_size()
+    }
+}
+
+fn diff_entries(
+    name: &OsString,
+    old: Option<&FileOrFolder>,
+    new: Option<&FileOrFolder>,
+    show_apparent_size: bool,
+) -> Option<FileOrFolder> {
+    match (old, new) {
+        (old, Some(FileOrFolder::Folder(new_folder))) => {
+            let old_folder = match old {
+                Some(FileOrFolder::Folder(old_folder)) => Some(old_folder),
+                _ => None,
+            };
+            diff_folders(name, old_folder, Some(new_folder), show_apparent_size)
+                .map(FileOrFolder::Folder)
+        }
+        (Some(FileOrFolder::Folder(old_folder)), None) => {
+            diff_folders(name, Some(old_folder), None, show_apparent_size).map(FileOrFolder::Folder)
+        }
+        (old, new) => {
+            // either both are files, or one of them does not exist or
+            // one was replaced with the other, in which case we count
+            // the whole old size as removed
+            let old_size = old.map_or(0, |old| size_of(old, show_apparent_size)) as i128;
+            let new_size = new.map_or(0, |new| size_of(new, show_apparent_size)) as i128;
+            let growth = new_size - old_size;
+            // the sizes we show are compared, even if the old scan showed the other ones
+            if growth == 0 {
+                None
+            } else {
+                Some(FileOrFolder::File(File {
+                    name: name.clone(),
+                    size: growth.unsigned_abs(),
+                    apparent_size: growth.unsigned_abs(),
+                    disk_size: growth.unsigned_abs(),
+                    growth: Some(growth),
+                    is_hard_linked: false,
+                    times: None,
+                    owner: None,
+                }))
+            }
+        }
+    }
+}
+
+fn diff_folders(
+    name: &OsString,
+    old: Option<&Folder>,
+    new: Option<&Folder>,
+    show_apparent_size: bool,
+) -> Option<Folder> {
+    let mut names: HashSet<&OsString> = HashSet::new();
+    for folder in old.iter().chain(new.iter()) {
+        names.extend(folder.contents.keys());
+    }
+    let mut diff = Folder::from(name.clone());
+    let mut growth = 0;
+    for name in names {
+        let old_entry = old.and_then(|old| old.contents.get(name));
+        let new_entry = new.and_then(|new| new.contents.get(name));
+        if let Some(entry) = diff_entries(name, old_entry, new_entry, show_apparent_size) {
+            // the size of the folder is the sum of all the changes inside it
+            // so that its contents could be laid out, while its growth is the
+            // sum of changes in both directions
+            diff.size += entry.size();
+            diff.apparent_size += entry.size();
+            diff.disk_size += entry.size();
+            diff.num_descendants += match &entry {
+                FileOrFolder::Folder(folder) => folder.num_descendants + 1,
+                FileOrFolder::File(_) => 1,
+            };
+            growth += entry.growth().unwrap_or(0);
+            diff.contents.insert(name.clone(), entry);
+        }
+    }
+    diff.growth = Some(growth);
+    if diff.contents.is_empty() {
+        None
+    } else {
+        Some(diff)
+    }
+}
+
+pub fn diff_file_trees(old: &FileTree, new: &FileTree) -> FileTree {
+    let old_base_folder = old.get_base_folder();
+    let new_base_folder = new.get_base_folder();
+    let base_folder = diff_folders(
+        &new_base_folder.name,
+        Some(old_base_folder),
+        Some(new_base_folder),
+        new.show_apparent_size,
+    )
+    .unwrap_or_else(|| {
+        let mut unchanged = Folder::from(new_base_folder.name.clone());
+        unchanged.growth = Some(0);
+        unchanged
+    });
+    let mut file_tree = FileTree::new(
+        base_folder,
+        new.path_in_filesystem.clone(),
+        new.show_apparent_size,
+    );
+    file_tree.failed_to_read = old.failed_to_read + new.failed_to_read;
+    file_tree.excluded = old.excluded + new.excluded;
+    file_tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::path::PathBuf;
+
+    #[test]
+    fn diff_growth_and_shrinkage() {
+        let mut old = Folder::new(&PathBuf::from("/tmp/base"));
+        old.add_file(PathBuf::from("grew/file1"), 100);
+        old.add_file(PathBuf::from("shrank/file2"), 300);
+        old.add_file(PathBuf::from("unchanged/file3"), 50);
+        old.add_file(PathBuf::from("removed"), 20);
+        let mut new = Folder::new(&PathBuf::from("/tmp/base"));
+        new.add_file(PathBuf::from("grew/file1"), 400);
+        new.add_file(PathBuf::from("shrank/file2"), 100);
+        new.add_file(PathBuf::from("unchanged/file3"), 50);
+        new.add_file(PathBuf::from("added"), 10);
+
+        let old = FileTree::new(old, PathBuf::from("/tmp/base"), true);
+        let new = FileTree::new(new, PathBuf::from("/tmp/base"), true);
+        let diff = diff_file_trees(&old, &new);
+        let base_folder = diff.get_base_folder();
+
+        assert_eq!(base_folder.growth, Some(90));
+        assert_eq!(base_folder.size, 530);
+        assert!(diff
+            .item_in_current_folder(&OsString::from("unchanged"))
+            .is_none());
+        let growth_of = |name: &str| {
+            diff.item_in_current_folder(&OsString::from(name))
+                .and_then(|item| item.growth())
+        };
+        assert_eq!(growth_of("grew"), Some(300));
+        assert_eq!(growth_of("shrank"), Some(-200));
+        assert_eq!(growth_of("removed"), Some(-20));
+        assert_eq!(growth_of("added"), Some(10));
+    }
+
+    #[test]
+    fn diff_scans_showing_different_sizes() {
+        let mut old = Folder::new(&PathBuf::from("/tmp/base"));
+        old.add_sized_file(PathBuf::from("unchanged"), 100, 4096);
+        old.add_sized_file(PathBuf::from("grew"), 100, 4096);
+        old.use_apparent_size(false);
+        let mut new = Folder::new(&PathBuf::from("/tmp/base"));
+        new.add_sized_file(PathBuf::from("unchanged"), 100, 4096);
+        new.add_sized_file(PathBuf::from("grew"), 200, 4096);
+        new.use_apparent_size(true);
+
+        let old = FileTree::new(old, PathBuf::from("/tmp/base"), false);
+        let new = FileTree::new(new, PathBuf::from("/tmp/base"), true);
+        let diff = diff_file_trees(&old, &new);
+
+        assert_eq!(diff.get_base_folder().growth, Some(100));
+        assert!(diff
+            .item_in_current_folder(&OsString::from("unchanged"))
+            .is_none());
+    }
+}
@@ -1,10 +1,16 @@
 use ::std::collections::{HashMap, VecDeque};
 use ::std::ffi::OsString;
 use ::std::fs::Metadata;
-use ::std::path::PathBuf;
+use ::std::path::{Path, PathBuf};
+use ::std::time::{SystemTime, UNIX_EPOCH};
 
 use ::filesize::PathExt;
 
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::get_owner_ids;
+#[cfg(target_os = "windows")]
+use crate::os::windows::get_owner_ids;
+
 #[derive(Debug, Clone)]
 pub enum FileOrFolder {
     Folder(Folder),
@@ -18,20 +24,158 @@ impl FileOrFolder {
             FileOrFolder::File(file) => file.size,
         }
     }
+    pub fn apparent_size(&self) -> u128 {
+        match self {
+            FileOrFolder::Folder(folder) => folder.apparent_size,
+            FileOrFolder::File(file) => file.apparent_size,
+        }
+    }
+    pub fn disk_size(&self) -> u128 {
+        match self {
+            FileOrFolder::Folder(folder) => folder.disk_size,
+            FileOrFolder::File(file) => file.disk_size,
+        }
+    }
+    pub fn growth(&self) -> Option<i128> {
+        match self {
+            FileOrFolder::Folder(folder) => folder.growth,
+            FileOrFolder::File(file) => file.growth,
+        }
+    }
+    pub fn is_hard_linked(&self) -> bool {
+        match self {
+            FileOrFolder::Folder(folder) => folder.has_hard_links,
+            FileOrFolder::File(file) => file.is_hard_linked,
+        }
+    }
+    pub fn newest_times(&self) -> Option<FileTimes> {
+        match self {
+            FileOrFolder::Folder(folder) => folder.newest_times,
+            FileOrFolder::File(file) => file.times,
+        }
+    }
+    pub fn oldest_times(&self) -> Option<FileTimes> {
+        match self {
+            FileOrFolder::Folder(folder) => folder.oldest_times,
+            FileOrFolder::File(file) => file.times,
+        }
+    }
+    pub fn last_touched(&self) -> Option<u64> {
+        self.newest_times().map(|times| times.last_touched())
+    }
+}
+
+fn seconds_since_epoch(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
+
+fn size_on_disk(path: &Path, metadata: &Metadata) -> u128 {
+    path.size_on_disk_fast(metadata).unwrap_or(metadata.len()) as u128
+}
+
+// when a file was last modified and accessed, in seconds since the unix epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileTimes {
+    pub modified: u64,
+    pub accessed: u64,
+}
+
+impl FileTimes {
+    pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+        let modified = seconds_since_epoch(metadata.modified().ok()?)?;
+        // not every filesystem keeps access times
+        let accessed = metadata
+            .accessed()
+            .ok()
+            .and_then(seconds_since_epoch)
+            .unwrap_or(modified);
+        Some(FileTimes { modified, accessed })
+    }
+    pub fn last_touched(&self) -> u64 {
+        self.modified.max(self.accessed)
+    }
+    fn newest(times: Option<Self>, other: Option<Self>) -> Option<Self> {
+        match (times, other) {
+            (Some(times), Some(other)) => Some(FileTimes {
+                modified: times.modified.max(other.modified),
+                accessed: times.accessed.max(other.accessed),
+            }),
+            (times, other) => times.or(other),
+        }
+    }
+    fn oldest(times: Option<Self>, other: Option<Self>) -> Option<Self> {
+        match (times, other) {
+            (Some(times), Some(other)) => Some(FileTimes {
+                modified: times.modified.min(other.modified),
+                accessed: times.accessed.min(other.accessed),
+            }),
+            (times, other) => times.or(other),
+        }
+    }
+}
+
+// the user and group a file belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileOwner {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl FileOwner {
+    pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+        let (uid, gid) = get_owner_ids(metadata)?;
+        Some(FileOwner { uid, gid })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HardLink {
+    // the file has only one link
+    None,
+    // the file has other links, and its size should be counted here
+    Counted,
+    // the file has other links, and its size was already counted in one of them
+    AlreadyCounted,
 }
 
 #[derive(Debug, Clone)]
 pub struct File {
     pub name: OsString,
+    // either the apparent size or the disk size, depending on which we show
     pub size: u128,
+    pub apparent_size: u128,
+    pub disk_size: u128,
+    // only present when comparing two scans, in which case size is
+    // how much this file changed regardless of direction
+    pub growth: Option<i128>,
+    // the data of this file is shared with other hard links
+    pub is_hard_linked: bool,
+    // not present when comparing two scans
+    pub times: Option<FileTimes>,
+    // not present when comparing two scans or on windows
+    pub owner: Option<FileOwner>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Folder {
     pub name: OsString,
     pub contents: HashMap<OsString, FileOrFolder>,
+    // either the apparent size or the disk size, depending on which we show
     pub size: u128,
+    pub apparent_size: u128,
+    pub disk_size: u128,
     pub num_descendants: u64,
+    pub growth: Option<i128>,
+    // a folder on another filesystem, whose contents were not scanned
+    pub is_mount_point: bool,
+    // some of the files in this folder share their data with other hard links
+    pub has_hard_links: bool,
+    // the latest and earliest times any of the files in this folder were
+    // modified and accessed
+    pub newest_times: Option<FileTimes>,
+    pub oldest_times: Option<FileTimes>,
 }
 
 impl From<OsString> for Folder {
@@ -40,7 +184,14 @@ impl From<OsString> for Folder {
             name,
             contents: HashMap::new(),
             size: 0,
+            apparent_size: 0,
+            disk_size: 0,
             num_descendants: 0,
+            growth: None,
+            is_mount_point: false,
+            has_hard_links: false,
+            newest_times: None,
+            oldest_times: None,
         }
     }
 }
@@ -51,7 +202,14 @@ impl Folder {
             name: base_folder_name.to_os_string(),
             contents: HashMap::new(),
             size: 0,
+            apparent_size: 0,
+            disk_size: 0,
             num_descendants: 0,
+            growth: None,
+            is_mount_point: false,
+            has_hard_links: false,
+            newest_times: None,
+            oldest_times: None,
         }
     }
 
@@ -60,22 +218,40 @@ impl Folder {
         entry_metadata: &Metadata,
         relative_path: PathBuf,
         show_apparent_size: bool,
+        hard_link: HardLink,
     ) {
         // apparent_size (named after the flag of the same name in 'du')
         // means "show the file size, rather than the actual space it takes on disk"
-        // these may differ (for example) in filesystems that use compression
+        // these may differ (for example) in filesystems that use compression,
+        // we keep both so that we can switch between them
         if entry_metadata.is_dir() {
             self.add_folder(relative_path);
+            return;
+        }
+        let (apparent_size, disk_size) = if hard_link == HardLink::AlreadyCounted {
+            (0, 0)
         } else {
-            let size = if show_apparent_size {
-                entry_metadata.len() as u128
+            (
+                entry_metadata.len() as u128,
+                size_on_disk(&relative_path, entry_metadata),
+            )
+        };
+        let file = File {
+            // named once it reaches the folder it is in
+            name: OsString::new(),
+            size: if show_apparent_size {
+                apparent_size
             } else {
-                relative_path
-                    .size_on_disk_fast(&entry_metadata)
-                    .unwrap_or(entry_metadata.len()) as u128
-            };
-            self.add_file(relative_path, size);
-        }
+                disk_size
+            },
+            apparent_size,
+            disk_size,
+            growth: None,
+            is_hard_linked: hard_link != HardLink::None,
+            times: FileTimes::from_metadata(entry_metadata),
+            owner: FileOwner::from_metadata(entry_metadata),
+        };
+        self.insert_file(relative_path, file);
     }
 
     pub fn add_folder(&mut self, path: PathBuf) {
@@ -109,7 +285,56 @@ impl Folder {
                 .insert(name.clone(), FileOrFolder::Folder(Folder::from(name)));
         }
     }
+    pub fn add_mount_point(&mut self, path: PathBuf) {
+        self.add_folder(path.clone());
+        let mut folder = self;
+        for name in path.iter() {
+            folder = match folder.contents.get_mut(name) {
+                Some(FileOrFolder::Folder(next_folder)) => next_folder,
+                _ => unreachable!("mount point was not added as a folder"),
+            };
+        }
+        folder.is_mount_point = true;
+    }
     pub fn add_file(&mut self, path: PathBuf, size: u128) {
+        self.add_sized_file(path, size, size);
+    }
+    pub fn add_sized_file(&mut self, path: PathBuf, apparent_size: u128, disk_size: u128) {
+        let file = File {
+            name: OsString::new(),
+            size: disk_size,
+            apparent_size,
+            disk_size,
+            growth: None,
+            is_hard_linked: false,
+            times: None,
+            owner: None,
+        };
+        self.insert_file(path, file);
+    }
+    fn add_times(&mut self, times: Option<FileTimes>) {
+        self.newest_times = FileTimes::newest(self.newest_times, times);
+        self.oldest_times = FileTimes::oldest(self.oldest_times, times);
+    }
+    pub fn update_times(&mut self) {
+        // the times of removed files cannot be taken out of the totals,
+        // so they are gathered again from what is left
+        self.newest_times = None;
+        self.oldest_times = None;
+        for file_or_folder in self.contents.values() {
+            self.newest_times = FileTimes::newest(self.newest_times, file_or_folder.newest_times());
+            self.oldest_times = FileTimes::oldest(self.oldest_times, file_or_folder.oldest_times());
+        }
+    }
+    fn count_file(&mut self, file: &File) {
+        self.size += file.size;
+        self.apparent_size += file.apparent_size;
+        self.disk_size += file.disk_size;
+        self.num_descendants += 1;
+        self.has_hard_links |= file.is_hard_linked;
+        self.add_times(file.times);
+    }
+    fn insert_file(&mut self, path: PathBuf, mut file: File) {
         let path_length = path.components().count();
         if path_length == 0 {
             return;
@@ -120,15 +345,14 @@ impl Folder {
                 .next()
                 .expect("could not get next path element for folder")
                 .to_os_string();
+            self.count_file(&file);
             let path_entry = self
                 .contents
                 .entry(name.clone())
                 .or_insert(FileOrFolder::Folder(Folder::from(name)));
-            self.size += size;
-            self.num_descendants += 1;
             match path_entry {
                 FileOrFolder::Folder(folder) => {
-                    folder.add_file(path.iter().skip(1).collect(), size);
+                    folder.insert_file(path.iter().skip(1).collect(), file);
                 }
                 _ => unreachable!("got a file in the middle of a path"),
             };
@@ -138,10 +362,26 @@ impl Folder {
                 .next()
                 .expect("could not get next path element for file")
                 .to_os_string();
-            self.size += size;
-            self.num_descendants += 1;
-            self.contents
-                .insert(name.clone(), FileOrFolder::File(File { name, size }));
+            self.count_file(&file);
+            file.name = name.clone();
+            self.contents.insert(name, FileOrFolder::File(file));
+        }
+    }
+    pub fn use_apparent_size(&mut self, show_apparent_size: bool) {
+        // both sizes are always kept up to date, so this only picks one
+        let pick = |apparent_size, disk_size| {
+            if show_apparent_size {
+                apparent_size
+            } else {
+                disk_size
+            }
+        };
+        self.size = pick(self.apparent_size, self.disk_size);
+        for file_or_folder in self.contents.values_mut() {
+            match file_or_folder {
+                FileOrFolder::Folder(folder) => folder.use_apparent_size(show_apparent_size),
+                FileOrFolder::File(file) => file.size = pick(file.apparent_size, file.disk_size),
+            }
         }
     }
     pub fn path(&self, mut folder_names: Vec<OsString>) -> Option<&FileOrFolder> {
@@ -155,6 +395,37 @@ impl Folder {
             Some(next_in_path)
         }
     }
+    pub fn replace_folder(&mut self, folder_names: &[OsString], mut folder: Folder) {
+        // the folders along the path grow or shrink by the difference
+        // between the replaced folder and the new one
+        let (next_name, folders_to_traverse) = folder_names
+            .split_first()
+            .expect("could not find next path folder");
+        let next_folder = match self.contents.get_mut(next_name) {
+            Some(FileOrFolder::Folder(next_folder)) => next_folder,
+            _ => panic!("could not find folder to replace"),
+        };
+        let (previous_size, previous_apparent_size, previous_disk_size, previous_descendants) = (
+            next_folder.size,
+            next_folder.apparent_size,
+            next_folder.disk_size,
+            next_folder.num_descendants,
+        );
+        if folders_to_traverse.is_empty() {
+            folder.is_mount_point = next_folder.is_mount_point;
+            *next_folder = folder;
+        } else {
+            next_folder.replace_folder(folders_to_traverse, folder);
+        }
+        self.size = self.size - previous_size + next_folder.size;
+        self.apparent_size =
+            self.apparent_size - previous_apparent_size + next_folder.apparent_size;
+        self.disk_size = self.disk_size - previous_disk_size + next_folder.disk_size;
+        self.num_descendants =
+            self.num_descendants - previous_descendants + next_folder.num_descendants;
+        self.has_hard_links |= next_folder.has_hard_links;
+        self.update_times();
+    }
     pub fn delete_path(&mut self, folder_names: &[OsString]) {
         // TODO: there are some needless allocations here, this is not terrible since
         // the deletion itself takes an order of magnitude longer, but it can be nice
@@ -164,30 +435,35 @@ impl Folder {
             let name = folder_names
                 .last()
                 .expect("could not find last item in path");
-            let removed_size = &self
-                .contents
-                .get(name)
-                .expect("could not find folder")
-                .size();
-            let removed_descendents = match &self.contents.get(name).expect("could not find folder")
-            {
+            let item_to_remove = self.contents.get(name).expect("could not find folder");
+            let removed_size = item_to_remove.size();
+            let removed_apparent_size = item_to_remove.apparent_size();
+            let removed_disk_size = item_to_remove.disk_size();
+            let removed_descendents = match item_to_remove {
                 FileOrFolder::Folder(folder) => folder.num_descendants,
                 FileOrFolder::File(_file) => 1,
             };
             self.size -= removed_size;
+            self.apparent_size -= removed_apparent_size;
+            self.disk_size -= removed_disk_size;
             self.num_descendants -= removed_descendents;
             self.contents.remove(name);
+            self.update_times();
         } else {
-            let (removed_size, removed_descendents) = {
+            let (removed_size, removed_apparent_size, removed_disk_size, removed_descendents) = {
                 let item_to_remove = self
                     .path(Vec::from(folders_to_traverse.clone()))
                     .expect("could not find item to delete");
-                let removed_size = item_to_remove.size();
                 let removed_descendents = match item_to_remove {
                     FileOrFolder::Folder(folder) => folder.num_descendants,
                     FileOrFolder::File(_file) => 1,
                 };
-                (removed_size, removed_descendents)
+                (
+                    item_to_remove.size(),
+                    item_to_remove.apparent_size(),
+                    item_to_remove.disk_size(),
+                    removed_descendents,
+                )
             };
             let next_name = folders_to_traverse
                 .pop_front()
@@ -199,6 +475,8 @@ impl Folder {
             match next_item {
                 FileOrFolder::Folder(folder) => {
                     self.size -= removed_size;
+                    self.apparent_size -= removed_apparent_size;
+                    self.disk_size -= removed_disk_size;
                     self.num_descendants -= removed_descendents;
                     folder.delete_path(&Vec::from(folders_to_traverse));
                 }
@@ -206,6 +484,7 @@ impl Folder {
                     panic!("got a file in the middle of a path");
                 }
             }
+            self.update_times();
         }
     }
 }
@@ -1,17 +1,57 @@
+use ::std::collections::HashMap;
 use ::std::ffi::{OsStr, OsString};
-use ::std::fs::Metadata;
+use ::std::fs::{self, Metadata};
+use ::std::io::{self, BufReader, BufWriter, Write};
 use ::std::path::{Path, PathBuf};
 
-use crate::state::files::{FileOrFolder, Folder};
+use crate::state::files::{
+    read_snapshot, write_snapshot, FileOrFolder, Folder, HardLink, SnapshotHeader,
+};
 use crate::state::FileToDelete;
 
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::get_hard_link_id;
+#[cfg(target_os = "windows")]
+use crate::os::windows::get_hard_link_id;
+
 pub struct FileTree {
     pub current_folder_names: Vec<OsString>,
     pub space_freed: u128,
     pub failed_to_read: u64,
+    // how many files and folders were left out of the scan (eg. with --exclude)
+    pub excluded: u64,
     pub path_in_filesystem: PathBuf,
+    pub show_apparent_size: bool,
+    // count the size of hard linked files once for every link, rather than
+    // only in the first link we found
+    pub count_hard_links: bool,
     base_folder: Folder,
-    show_apparent_size: bool,
+    // (device, inode) of the hard linked files we already counted, and the link we counted
+    hard_links: HashMap<(u64, u64), PathBuf>,
+    // how many entries we failed to read or left out in each folder, so
+    // that they are no longer counted once the folder is rescanned
+    failed_to_read_in: HashMap<PathBuf, u64>,
+    excluded_from: HashMap<PathBuf, u64>,
+    // goes up whenever the files and folders (or their sizes) change
+    changes: u64,
+}
+
+fn add_count(counts: &mut HashMap<PathBuf, u64>, folder: PathBuf, count: u64) {
+    *counts.entry(folder).or_insert(0) += count;
+}
+
+fn take_counts_in(counts: &mut HashMap<PathBuf, u64>, path: &Path) -> u64 {
+    // removes the counts of path and every folder in it, returning their total
+    let mut taken = 0;
+    counts.retain(|folder, count| {
+        if folder.starts_with(path) {
+            taken += *count;
+            false
+        } else {
+            true
+        }
+    });
+    taken
 }
 
 impl FileTree {
@@ -22,12 +62,64 @@ impl FileTree {
             path_in_filesystem,
             space_freed: 0,
             failed_to_read: 0,
+            excluded: 0,
             show_apparent_size,
+            count_hard_links: false,
+            hard_links: HashMap::new(),
+            failed_to_read_in: HashMap::new(),
+            excluded_from: HashMap::new(),
+            changes: 0,
         }
     }
+    pub fn subfolder_to_rescan(&self, path: PathBuf) -> FileTree {
+        // an empty file tree for a new scan of one of our folders, which knows
+        // of the hard links we counted elsewhere so it does not count them again
+        let mut file_tree = FileTree::new(Folder::new(&path), path, self.show_apparent_size);
+        file_tree.count_hard_links = self.count_hard_links;
+        file_tree.hard_links = self
+            .hard_links
+            .iter()
+            .filter(|(_, counted_link)| !counted_link.starts_with(&file_tree.path_in_filesystem))
+            .map(|(hard_link_id, counted_link)| (*hard_link_id, counted_link.clone()))
+            .collect();
+        file_tree
+    }
+    fn folder_in_tree(&self, folder: &Path) -> PathBuf {
+        // counts from outside our base folder (eg. its own metadata) are kept with it
+        if folder.starts_with(&self.path_in_filesystem) {
+            folder.to_path_buf()
+        } else {
+            self.path_in_filesystem.clone()
+        }
+    }
+    pub fn add_failed_to_read(&mut self, folder: &Path) {
+        // folder is where the entry we failed to read is, or the folder we failed to list
+        self.failed_to_read += 1;
+        let folder = self.folder_in_tree(folder);
+        add_count(&mut self.failed_to_read_in, folder, 1);
+    }
+    pub fn add_excluded(&mut self, folder: &Path) {
+        self.excluded += 1;
+        let folder = self.folder_in_tree(folder);
+        add_count(&mut self.excluded_from, folder, 1);
+    }
+    pub fn get_base_folder(&self) -> &Folder {
+        &self.base_folder
+    }
+    pub fn is_diff(&self) -> bool {
+        self.base_folder.growth.is_some()
+    }
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
     pub fn get_total_size(&self) -> u128 {
         self.base_folder.size
     }
+    pub fn set_show_apparent_size(&mut self, show_apparent_size: bool) {
+        self.changes += 1;
+        self.show_apparent_size = show_apparent_size;
+        self.base_folder.use_apparent_size(show_apparent_size);
+    }
     pub fn get_total_descendants(&self) -> u64 {
         self.base_folder.num_descendants
     }
@@ -61,21 +153,256 @@ impl FileTree {
     pub fn enter_folder(&mut self, folder_name: &OsStr) {
         self.current_folder_names.push(folder_name.to_os_string());
     }
+    pub fn set_current_folder(&mut self, folder_names: &[OsString]) -> bool {
+        // false => there is no such folder
+        match self.find_entry(folder_names) {
+            Some(FileOrFolder::Folder(_)) => {}
+            None if folder_names.is_empty() => {}
+            _ => return false,
+        }
+        self.current_folder_names = folder_names.to_vec();
+        true
+    }
     pub fn leave_folder(&mut self) -> bool {
         // true => succeeded, false => at base folder
         self.current_folder_names.pop().is_some()
     }
     pub fn delete_file(&mut self, file_to_delete: &FileToDelete) {
+        self.changes += 1;
         let path_to_delete = &file_to_delete.path_to_file;
         self.base_folder.delete_path(&path_to_delete);
     }
-    pub fn add_entry(&mut self, entry_metadata: &Metadata, entry_full_path: &Path) {
+    fn relative_path(&self, entry_full_path: &Path) -> PathBuf {
         let base_path_length = self.path_in_filesystem.components().count();
         let mut relative_path = PathBuf::new();
         for dir in entry_full_path.components().skip(base_path_length) {
             relative_path.push(dir);
         }
-        self.base_folder
-            .add_entry(entry_metadata, relative_path, self.show_apparent_size);
+        relative_path
+    }
+    fn folder_names(&self, entry_full_path: &Path) -> Vec<OsString> {
+        self.relative_path(entry_full_path)
+            .iter()
+            .map(|name| name.to_os_string())
+            .collect()
+    }
+    fn find_entry(&self, folder_names: &[OsString]) -> Option<&FileOrFolder> {
+        // unlike Folder::path, this does not stop at a file in the middle of the path
+        let (name, parent_names) = folder_names.split_last()?;
+        let mut parent = &self.base_folder;
+        for folder_name in parent_names {
+            parent = match parent.contents.get(folder_name)? {
+                FileOrFolder::Folder(folder) => folder,
+                FileOrFolder::File(_) => return None,
+            };
+        }
+        parent.contents.get(name)
+    }
+    pub fn find_path(&self, entry_full_path: &Path) -> Option<&FileOrFolder> {
+        self.find_entry(&self.folder_names(entry_full_path))
+    }
+    pub fn add_entry(&mut self, entry_metadata: &Metadata, entry_full_path: &Path) {
+        self.changes += 1;
+        let relative_path = self.relative_path(entry_full_path);
+        let hard_link = match get_hard_link_id(entry_metadata) {
+            Some(_) if entry_metadata.is_dir() => HardLink::None,
+            Some(_) if self.count_hard_links => HardLink::Counted,
+            Some(hard_link_id) if !self.hard_links.contains_key(&hard_link_id) => {
+                self.hard_links
+                    .insert(hard_link_id, entry_full_path.to_path_buf());
+                HardLink::Counted
+            }
+            Some(_) => HardLink::AlreadyCounted,
+            None => HardLink::None,
+        };
+        self.base_folder.add_entry(
+            entry_metadata,
+            relative_path,
+            self.show_apparent_size,
+            hard_link,
+        );
+    }
+    pub fn add_mount_point(&mut self, entry_full_path: &Path) {
+        self.changes += 1;
+        let relative_path = self.relative_path(entry_full_path);
+        self.base_folder.add_mount_point(relative_path);
+    }
+    pub fn update_entry(&mut self, entry_metadata: &Metadata, entry_full_path: &Path) {
+        // this can be called more than once for the same entry (eg. when it
+        // changes while being scanned), so we replace whatever we know of it
+        let folder_names = self.folder_names(entry_full_path);
+        if folder_names.is_empty() {
+            return;
+        }
+        match self.find_entry(&folder_names) {
+            Some(FileOrFolder::Folder(_)) if entry_metadata.is_dir() => {
+                // the size of a folder is the size of its contents,
+                // which are updated on their own
+                return;
+            }
+            Some(FileOrFolder::File(file)) => {
+                if file.is_hard_linked && file.size > 0 {
+                    // this link is where the shared data was counted,
+                    // so it should be counted again once it's replaced
+                    if let Some(hard_link_id) = get_hard_link_id(entry_metadata) {
+                        self.hard_links.remove(&hard_link_id);
+                    }
+                }
+                self.base_folder.delete_path(&folder_names);
+            }
+            Some(FileOrFolder::Folder(_)) => self.base_folder.delete_path(&folder_names),
+            None => {}
+        }
+        self.add_entry(entry_metadata, entry_full_path);
+    }
+    pub fn remove_entry(&mut self, entry_full_path: &Path) -> usize {
+        // returns how many folders we had to leave because they were removed
+        let folder_names = self.folder_names(entry_full_path);
+        if self.find_entry(&folder_names).is_none() {
+            return 0;
+        }
+        self.changes += 1;
+        self.base_folder.delete_path(&folder_names);
+        self.leave_removed_folders()
+    }
+    pub fn replace_folder(&mut self, rescanned: FileTree) -> usize {
+        // rescanned is a new scan of one of our folders (or of the base folder),
+        // returns how many folders we had to leave because they are no longer there
+        let folder_names = self.folder_names(&rescanned.path_in_filesystem);
+        if folder_names.is_empty() {
+            self.base_folder = rescanned.base_folder;
+        } else if let Some(FileOrFolder::Folder(_)) = self.find_entry(&folder_names) {
+            self.base_folder
+                .replace_folder(&folder_names, rescanned.base_folder);
+        } else {
+            // it was removed while being scanned
+            return 0;
+        }
+        self.changes += 1;
+        // what we knew of the folder before is replaced by what the rescan found
+        let path = &rescanned.path_in_filesystem;
+        self.hard_links
+            .retain(|_, counted_link| !counted_link.starts_with(path));
+        self.hard_links.extend(
+            rescanned
+                .hard_links
+                .into_iter()
+                .filter(|(_, counted_link)| counted_link.starts_with(path)),
+        );
+        self.failed_to_read = self
+            .failed_to_read
+            .saturating_sub(take_counts_in(&mut self.failed_to_read_in, path))
+            + rescanned.failed_to_read;
+        for (folder, count) in rescanned.failed_to_read_in {
+            add_count(&mut self.failed_to_read_in, folder, count);
+        }
+        self.excluded = self
+            .excluded
+            .saturating_sub(take_counts_in(&mut self.excluded_from, path))
+            + rescanned.excluded;
+        for (folder, count) in rescanned.excluded_from {
+            add_count(&mut self.excluded_from, folder, count);
+        }
+        self.leave_removed_folders()
+    }
+    fn leave_removed_folders(&mut self) -> usize {
+        let mut left_folders = 0;
+        while !self.current_folder_names.is_empty() {
+            match self.find_entry(&self.current_folder_names) {
+                Some(FileOrFolder::Folder(_)) => break,
+                _ => {
+                    self.current_folder_names.pop();
+                    left_folders += 1;
+                }
+            }
+        }
+        left_folders
+    }
+    pub fn load_snapshot(snapshot_path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(fs::File::open(snapshot_path)?);
+        let (header, base_folder) = read_snapshot(&mut reader)?;
+        let mut file_tree = FileTree::new(
+            base_folder,
+            header.path_in_filesystem,
+            header.show_apparent_size,
+        );
+        file_tree.failed_to_read = header.failed_to_read;
+        file_tree.excluded = header.excluded;
+        Ok(file_tree)
+    }
+    pub fn save_snapshot(&self, snapshot_path: &Path) -> io::Result<()> {
+        let header = SnapshotHeader {
+            path_in_filesystem: self.path_in_filesystem.clone(),
+            show_apparent_size: self.show_apparent_size,
+            failed_to_read: self.failed_to_read,
+            excluded: self.excluded,
+        };
+        let mut writer = BufWriter::new(fs::File::create(snapshot_path)?);
+        write_snapshot(&mut writer, &header, &self.base_folder)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::fs::{create_dir_all, remove_dir_all, symlink_metadata, write};
+
+    #[test]
+    fn update_and_remove_entries() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/file_tree_update");
+        remove_dir_all(&path).ok();
+        create_dir_all(path.join("subfolder")).expect("failed to create folder");
+        write(path.join("subfolder/file"), vec![0; 1000]).expect("failed to create file");
+
+        let mut file_tree = FileTree::new(Folder::new(&path), path.clone(), true);
+        for entry_path in &[path.join("subfolder"), path.join("subfolder/file")] {
+            let metadata = symlink_metadata(entry_path).expect("failed to read metadata");
+            file_tree.add_entry(&metadata, entry_path);
+        }
+        file_tree.enter_folder(OsStr::new("subfolder"));
+
+        write(path.join("subfolder/file"), vec![0; 3000]).expect("failed to write file");
+        let metadata =
+            symlink_metadata(path.join("subfolder/file")).expect("failed to read metadata");
+        // the same change can be reported more than once
+        file_tree.update_entry(&metadata, &path.join("subfolder/file"));
+        file_tree.update_entry(&metadata, &path.join("subfolder/file"));
+        assert_eq!(file_tree.get_total_size(), 3000);
+        assert_eq!(file_tree.get_total_descendants(), 2);
+
+        remove_dir_all(path.join("subfolder")).expect("failed to remove folder");
+        assert_eq!(file_tree.remove_entry(&path.join("subfolder/file")), 0);
+        assert_eq!(file_tree.remove_entry(&path.join("subfolder")), 1);
+        assert_eq!(file_tree.remove_entry(&path.join("subfolder")), 0);
+        remove_dir_all(&path).ok();
+
+        assert_eq!(file_tree.get_total_size(), 0);
+        assert!(file_tree.current_folder_names.is_empty());
+    }
+
+    #[test]
+    fn switch_between_apparent_and_disk_size() {
+        let path = PathBuf::from("/tmp/base");
+        let mut base_folder = Folder::new(&path);
+        base_folder.add_sized_file(PathBuf::from("subfolder/sparse"), 1_000_000, 4096);
+        base_folder.add_sized_file(PathBuf::from("subfolder/small"), 100, 4096);
+        base_folder.add_sized_file(PathBuf::from("file"), 8192, 8192);
+        let mut file_tree = FileTree::new(base_folder, path, false);
+        assert_eq!(file_tree.get_total_size(), 16384);
+
+        file_tree.set_show_apparent_size(true);
+        assert_eq!(file_tree.get_total_size(), 1_008_292);
+        file_tree.enter_folder(OsStr::new("subfolder"));
+        assert_eq!(file_tree.get_current_folder_size(), 1_000_100);
+
+        // both sizes are kept up to date when files are removed
+        file_tree
+            .base_folder
+            .delete_path(&[OsString::from("subfolder"), OsString::from("small")]);
+        assert_eq!(file_tree.get_total_size(), 1_008_192);
+        file_tree.set_show_apparent_size(false);
+        assert_eq!(file_tree.get_total_size(), 12288);
+        assert_eq!(file_tree.get_current_folder_size(), 4096);
     }
 }
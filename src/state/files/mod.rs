@@ -1,5 +1,9 @@
+mod diff;
 mod file_or_folder;
 mod file_tree;
+mod snapshot;
 
+pub use diff::*;
 pub use file_or_folder::*;
 pub use file_tree::*;
+pub use snapshot::*;
@@ -0,0 +1,543 @@
+use ::std::collections::HashMap;
+use ::std::ffi::{OsStr, OsString};
+use ::std::io::{self, Read, Write};
+use ::std::path::{PathBuf, MAIN_SEPARATOR};
+
+use crate::state::files::{File, FileOrFolder, FileOwner, FileTimes, Folder};
+
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::{os_str_to_bytes, os_string_from_bytes};
+#[cfg(target_os = "windows")]
+use crate::os::windows::{os_str_to_bytes, os_string_from_bytes};
+
+// a snapshot file starts with these bytes followed by the format version,
+// the version should be bumped whenever the layout below changes so that
+// we refuse to load snapshots we do not know how to read
+const SNAPSHOT_MAGIC: &[u8] = b"diskonaut-snapshot";
+const SNAPSHOT_VERSION: u32 = 4;
+
+const FILE_TAG: u8 = 0;
+const FOLDER_TAG: u8 = 1;
+// mount points are always empty, so only their name is written
+const MOUNT_POINT_TAG: u8 = 2;
+const HARD_LINKED_FILE_TAG: u8 = 3;
+
+// far deeper than any real folder tree, but shallow enough
+// that reading (or later walking) it cannot overflow the stack
+const MAX_DEPTH: usize = 512;
+
+pub struct SnapshotHeader {
+    pub path_in_filesystem: PathBuf,
+    pub show_apparent_size: bool,
+    pub failed_to_read: u64,
+    pub excluded: u64,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_u128(writer: &mut impl Write, value: u128) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_times(writer: &mut impl Write, times: Option<FileTimes>) -> io::Result<()> {
+    match times {
+        Some(times) => {
+            writer.write_all(&[1])?;
+            write_u64(writer, times.modified)?;
+            write_u64(writer, times.accessed)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn write_owner(writer: &mut impl Write, owner: Option<FileOwner>) -> io::Result<()> {
+    match owner {
+        Some(owner) => {
+            writer.write_all(&[1])?;
+            writer.write_all(&owner.uid.to_le_bytes())?;
+            writer.write_all(&owner.gid.to_le_bytes())
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn write_os_str(writer: &mut impl Write, os_str: &OsStr) -> io::Result<()> {
+    let bytes = os_str_to_bytes(os_str);
+    write_u64(writer, bytes.len() as u64)?;
+    writer.write_all(&bytes)
+}
+
+fn write_folder(writer: &mut impl Write, folder: &Folder) -> io::Result<()> {
+    write_os_str(writer, &folder.name)?;
+    write_u128(writer, folder.apparent_size)?;
+    write_u128(writer, folder.disk_size)?;
+    write_u64(writer, folder.num_descendants)?;
+    write_u64(writer, folder.contents.len() as u64)?;
+    for file_or_folder in folder.contents.values() {
+        match file_or_folder {
+            FileOrFolder::Folder(folder) if folder.is_mount_point => {
+                writer.write_all(&[MOUNT_POINT_TAG])?;
+                write_os_str(writer, &folder.name)?;
+            }
+            FileOrFolder::Folder(folder) => {
+                writer.write_all(&[FOLDER_TAG])?;
+                write_folder(writer, folder)?;
+            }
+            FileOrFolder::File(file) => {
+                let tag = if file.is_hard_linked {
+                    HARD_LINKED_FILE_TAG
+                } else {
+                    FILE_TAG
+                };
+                writer.write_all(&[tag])?;
+                write_os_str(writer, &file.name)?;
+                write_u128(writer, file.apparent_size)?;
+                write_u128(writer, file.disk_size)?;
+                write_times(writer, file.times)?;
+                write_owner(writer, file.owner)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_u128(reader: &mut impl Read) -> io::Result<u128> {
+    let mut buf = [0; 16];
+    reader.read_exact(&mut buf)?;
+    Ok(u128::from_le_bytes(buf))
+}
+
+fn read_times(reader: &mut impl Read) -> io::Result<Option<FileTimes>> {
+    match read_u8(reader)? {
+        0 => Ok(None),
+        _ => Ok(Some(FileTimes {
+            modified: read_u64(reader)?,
+            accessed: read_u64(reader)?,
+        })),
+    }
+}
+
+fn read_owner(reader: &mut impl Read) -> io::Result<Option<FileOwner>> {
+    match read_u8(reader)? {
+        0 => Ok(None),
+        _ => Ok(Some(FileOwner {
+            uid: read_u32(reader)?,
+            gid: read_u32(reader)?,
+        })),
+    }
+}
+
+fn read_os_string(reader: &mut impl Read) -> io::Result<OsString> {
+    let len = read_u64(reader)?;
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(invalid_data("snapshot is truncated"));
+    }
+    Ok(os_string_from_bytes(bytes))
+}
+
+fn validate_name(name: &OsStr) -> io::Result<()> {
+    // names are joined to paths we delete, so they must not lead anywhere else
+    let bytes = os_str_to_bytes(name);
+    let is_valid = !bytes.is_empty()
+        && bytes != b"."
+        && bytes != b".."
+        && !bytes.contains(&b'/')
+        && !bytes.contains(&(MAIN_SEPARATOR as u8));
+    if is_valid {
+        Ok(())
+    } else {
+        Err(invalid_data(&format!(
+            "snapshot contains an invalid name: {:?}",
+            name
+        )))
+    }
+}
+
+fn add_size(total: &mut u128, size: u128) -> io::Result<()> {
+    *total = total
+        .checked_add(size)
+        .ok_or_else(|| invalid_data("snapshot contains impossible sizes"))?;
+    Ok(())
+}
+
+fn read_folder(reader: &mut impl Read, depth: usize) -> io::Result<Folder> {
+    // sizes are set to one of the two we read once the whole snapshot is read
+    if depth > MAX_DEPTH {
+        return Err(invalid_data("snapshot contains folders nested too deep"));
+    }
+    let name = read_os_string(reader)?;
+    let apparent_size = read_u128(reader)?;
+    let disk_size = read_u128(reader)?;
+    let num_descendants = read_u64(reader)?;
+    let contents_len = read_u64(reader)?;
+    let mut contents = HashMap::new();
+    let mut has_hard_links = false;
+    // what we read from the folder's contents, which its totals have to add up to
+    let mut contents_apparent_size = 0;
+    let mut contents_disk_size = 0;
+    let mut contents_descendants: u64 = 0;
+    for _ in 0..contents_len {
+        let file_or_folder = match read_u8(reader)? {
+            FOLDER_TAG => FileOrFolder::Folder(read_folder(reader, depth + 1)?),
+            MOUNT_POINT_TAG => {
+                let mut mount_point = Folder::from(read_os_string(reader)?);
+                mount_point.is_mount_point = true;
+                FileOrFolder::Folder(mount_point)
+            }
+            tag @ FILE_TAG | tag @ HARD_LINKED_FILE_TAG => {
+                let name = read_os_string(reader)?;
+                let apparent_size = read_u128(reader)?;
+                let disk_size = read_u128(reader)?;
+                let times = read_times(reader)?;
+                let owner = read_owner(reader)?;
+                FileOrFolder::File(File {
+                    name,
+                    size: 0,
+                    apparent_size,
+                    disk_size,
+                    growth: None,
+                    is_hard_linked: tag == HARD_LINKED_FILE_TAG,
+                    times,
+                    owner,
+                })
+            }
+            _ => return Err(invalid_data("snapshot contains an unknown entry type")),
+        };
+        has_hard_links |= file_or_folder.is_hard_linked();
+        add_size(&mut contents_apparent_size, file_or_folder.apparent_size())?;
+        add_size(&mut contents_disk_size, file_or_folder.disk_size())?;
+        let (name, descendants) = match &file_or_folder {
+            FileOrFolder::Folder(folder) => (folder.name.clone(), folder.num_descendants),
+            FileOrFolder::File(file) => (file.name.clone(), 1),
+        };
+        contents_descendants = descendants
+            .checked_add(contents_descendants)
+            .ok_or_else(|| invalid_data("snapshot contains impossible file counts"))?;
+        validate_name(&name)?;
+        if contents.insert(name, file_or_folder).is_some() {
+            return Err(invalid_data("snapshot contains the same name twice"));
+        }
+    }
+    // deleting something takes its sizes and counts out of the folders it is in,
+    // so they have to add up (the folders themselves are not always counted)
+    if apparent_size != contents_apparent_size
+        || disk_size != contents_disk_size
+        || num_descendants < contents_descendants
+    {
+        return Err(invalid_data(
+            "snapshot contains folders whose sizes do not add up",
+        ));
+    }
+    let mut folder = Folder {
+        name,
+        contents,
+        size: 0,
+        apparent_size,
+        disk_size,
+        num_descendants,
+        growth: None,
+        is_mount_point: false,
+        has_hard_links,
+        newest_times: None,
+        oldest_times: None,
+    };
+    folder.update_times();
+    Ok(folder)
+}
+
+pub fn write_snapshot(
+    writer: &mut impl Write,
+    header: &SnapshotHeader,
+    base_folder: &Folder,
+) -> io::Result<()> {
+    writer.write_all(SNAPSHOT_MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    write_os_str(writer, header.path_in_filesystem.as_os_str())?;
+    writer.write_all(&[header.show_apparent_size as u8])?;
+    write_u64(writer, header.failed_to_read)?;
+    write_u64(writer, header.excluded)?;
+    write_folder(writer, base_folder)
+}
+
+pub fn read_snapshot(reader: &mut impl Read) -> io::Result<(SnapshotHeader, Folder)> {
+    let mut magic = vec![0; SNAPSHOT_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(invalid_data("not a diskonaut snapshot"));
+    }
+    let version = read_u32(reader)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(invalid_data(&format!(
+            "unsupported snapshot version {} (expected {})",
+            version, SNAPSHOT_VERSION
+        )));
+    }
+    let path_in_filesystem = PathBuf::from(read_os_string(reader)?);
+    // the paths we delete are joined to it, so it cannot depend on where we run
+    if !path_in_filesystem.is_absolute() {
+        return Err(invalid_data(
+            "snapshot is of a folder without an absolute path",
+        ));
+    }
+    let show_apparent_size = read_u8(reader)? != 0;
+    let failed_to_read = read_u64(reader)?;
+    let excluded = read_u64(reader)?;
+    let mut base_folder = read_folder(reader, 0)?;
+    base_folder.use_apparent_size(show_apparent_size);
+    let header = SnapshotHeader {
+        path_in_filesystem,
+        show_apparent_size,
+        failed_to_read,
+        excluded,
+    };
+    Ok((header, base_folder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::thread;
+
+    fn set_times_and_owner(folder: &mut Folder, name: &str, times: FileTimes) {
+        match folder.contents.get_mut(&OsString::from(name)) {
+            Some(FileOrFolder::File(file)) => {
+                file.times = Some(times);
+                file.owner = Some(FileOwner {
+                    uid: 1000,
+                    gid: 100,
+                });
+            }
+            _ => panic!("{} is not a file", name),
+        }
+        folder.update_times();
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut base_folder = Folder::new(&PathBuf::from("/tmp/base"));
+        base_folder.add_file(PathBuf::from("subfolder/file1"), 4096);
+        base_folder.add_sized_file(PathBuf::from("file2"), 8192, 4096);
+        base_folder.add_folder(PathBuf::from("empty_folder"));
+        base_folder.add_mount_point(PathBuf::from("subfolder/mount_point"));
+        let old_times = FileTimes {
+            modified: 1_000_000,
+            accessed: 1_500_000,
+        };
+        let new_times = FileTimes {
+            modified: 2_000_000,
+            accessed: 1_200_000,
+        };
+        set_times_and_owner(&mut base_folder, "file2", old_times);
+        match base_folder.contents.get_mut(&OsString::from("subfolder")) {
+            Some(FileOrFolder::Folder(subfolder)) => {
+                set_times_and_owner(subfolder, "file1", new_times)
+            }
+            _ => panic!("subfolder is not a folder"),
+        }
+        base_folder.update_times();
+        let header = SnapshotHeader {
+            path_in_filesystem: PathBuf::from("/tmp/base"),
+            show_apparent_size: true,
+            failed_to_read: 3,
+            excluded: 2,
+        };
+
+        let mut bytes = vec![];
+        write_snapshot(&mut bytes, &header, &base_folder).expect("failed to write snapshot");
+        let (loaded_header, loaded_folder) =
+            read_snapshot(&mut &bytes[..]).expect("failed to read snapshot");
+
+        assert_eq!(loaded_header.path_in_filesystem, PathBuf::from("/tmp/base"));
+        assert!(loaded_header.show_apparent_size);
+        assert_eq!(loaded_header.failed_to_read, 3);
+        assert_eq!(loaded_header.excluded, 2);
+        assert_eq!(loaded_folder.size, 12288);
+        assert_eq!(loaded_folder.disk_size, 8192);
+        assert_eq!(loaded_folder.num_descendants, 4);
+        assert_eq!(
+            loaded_folder.newest_times,
+            Some(FileTimes {
+                modified: 2_000_000,
+                accessed: 1_500_000,
+            })
+        );
+        assert_eq!(
+            loaded_folder.oldest_times,
+            Some(FileTimes {
+                modified: 1_000_000,
+                accessed: 1_200_000,
+            })
+        );
+        match loaded_folder.path(vec![OsString::from("file2")]) {
+            Some(FileOrFolder::File(file)) => {
+                assert_eq!(file.times, Some(old_times));
+                assert_eq!(
+                    file.owner,
+                    Some(FileOwner {
+                        uid: 1000,
+                        gid: 100,
+                    })
+                );
+            }
+            _ => panic!("file2 was not loaded as a file"),
+        }
+        match loaded_folder.path(vec![OsString::from("subfolder")]) {
+            Some(FileOrFolder::Folder(subfolder)) => {
+                assert_eq!(subfolder.size, 4096);
+                assert_eq!(subfolder.num_descendants, 2);
+                assert!(!subfolder.is_mount_point);
+            }
+            _ => panic!("subfolder was not loaded as a folder"),
+        }
+        match loaded_folder.path(vec![
+            OsString::from("subfolder"),
+            OsString::from("mount_point"),
+        ]) {
+            Some(FileOrFolder::Folder(mount_point)) => assert!(mount_point.is_mount_point),
+            _ => panic!("mount point was not loaded as a folder"),
+        }
+    }
+
+    #[test]
+    fn snapshot_with_wrong_version() {
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.extend_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        let error = read_snapshot(&mut &bytes[..])
+            .err()
+            .expect("loaded a snapshot with an unknown version");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    fn read_error(base_folder: &Folder) -> io::Error {
+        let header = SnapshotHeader {
+            path_in_filesystem: PathBuf::from("/tmp/base"),
+            show_apparent_size: true,
+            failed_to_read: 0,
+            excluded: 0,
+        };
+        let mut bytes = vec![];
+        write_snapshot(&mut bytes, &header, base_folder).expect("failed to write snapshot");
+        read_snapshot(&mut &bytes[..])
+            .err()
+            .expect("loaded an invalid snapshot")
+    }
+
+    #[test]
+    fn reject_invalid_snapshots() {
+        let mut base_folder = Folder::new(&PathBuf::from("/tmp/base"));
+        base_folder.add_file(PathBuf::from("subfolder/file1"), 4096);
+        let mut with_parent_name = base_folder.clone();
+        match with_parent_name
+            .contents
+            .get_mut(&OsString::from("subfolder"))
+        {
+            Some(FileOrFolder::Folder(subfolder)) => subfolder.name = OsString::from(".."),
+            _ => panic!("subfolder is not a folder"),
+        }
+        let mut with_wrong_size = base_folder.clone();
+        with_wrong_size.apparent_size += 1;
+        let mut with_wrong_count = base_folder.clone();
+        match with_wrong_count
+            .contents
+            .get_mut(&OsString::from("subfolder"))
+        {
+            Some(FileOrFolder::Folder(subfolder)) => subfolder.num_descendants = 0,
+            _ => panic!("subfolder is not a folder"),
+        }
+
+        assert_eq!(
+            read_error(&with_parent_name).kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            read_error(&with_wrong_size).kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            read_error(&with_wrong_count).kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn reject_snapshots_of_relative_paths() {
+        let header = SnapshotHeader {
+            path_in_filesystem: PathBuf::from("base"),
+            show_apparent_size: true,
+            failed_to_read: 0,
+            excluded: 0,
+        };
+        let mut bytes = vec![];
+        write_snapshot(&mut bytes, &header, &Folder::new(&PathBuf::from("base")))
+            .expect("failed to write snapshot");
+        let error = read_snapshot(&mut &bytes[..])
+            .err()
+            .expect("loaded a snapshot of a relative path");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reject_snapshots_nested_too_deep() {
+        // written by hand, since a folder this deep is too deep to build
+        let header = SnapshotHeader {
+            path_in_filesystem: PathBuf::from("/tmp/base"),
+            show_apparent_size: true,
+            failed_to_read: 0,
+            excluded: 0,
+        };
+        let mut bytes = vec![];
+        write_snapshot(
+            &mut bytes,
+            &header,
+            &Folder::new(&PathBuf::from("/tmp/base")),
+        )
+        .expect("failed to write snapshot");
+        // the base folder's (empty) contents are replaced by a chain of folders
+        bytes.truncate(bytes.len() - 8);
+        for depth in 0..=MAX_DEPTH + 1 {
+            write_u64(&mut bytes, 1).expect("failed to write snapshot");
+            bytes.push(FOLDER_TAG);
+            write_os_str(&mut bytes, OsStr::new("folder")).expect("failed to write snapshot");
+            write_u128(&mut bytes, 0).expect("failed to write snapshot");
+            write_u128(&mut bytes, 0).expect("failed to write snapshot");
+            write_u64(&mut bytes, (MAX_DEPTH + 1 - depth) as u64)
+                .expect("failed to write snapshot");
+        }
+        write_u64(&mut bytes, 0).expect("failed to write snapshot");
+        // snapshots are loaded on the main thread, which has a bigger stack than tests do
+        let error = thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(move || read_snapshot(&mut &bytes[..]).err())
+            .expect("failed to start reading snapshot")
+            .join()
+            .expect("failed to read snapshot")
+            .expect("loaded a snapshot nested too deep");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
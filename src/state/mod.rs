@@ -1,5 +1,7 @@
 pub mod file_to_delete;
 pub mod files;
+pub mod path_prompt;
+pub mod search;
 pub mod tiles;
 pub mod ui_effects;
 
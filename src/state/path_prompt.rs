@@ -0,0 +1,187 @@
+use ::std::ffi::OsString;
+use ::std::path::{Component, Path};
+
+use crate::state::files::{FileOrFolder, Folder};
+
+// a path typed in to go straight to, relative to the scanned folder
+#[derive(Clone, Debug)]
+pub struct PathPrompt {
+    pub input: String,
+    // the names the last <TAB> could have completed to
+    pub completions: Vec<String>,
+    // we tried to go to the input, but there is no such file or folder
+    pub not_found: bool,
+}
+
+impl PathPrompt {
+    pub fn new() -> Self {
+        PathPrompt {
+            input: String::new(),
+            completions: vec![],
+            not_found: false,
+        }
+    }
+    pub fn push(&mut self, character: char) {
+        self.input.push(character);
+        self.completions.clear();
+        self.not_found = false;
+    }
+    pub fn pop(&mut self) {
+        self.input.pop();
+        self.completions.clear();
+        self.not_found = false;
+    }
+    pub fn folder_names(&self, base_path: &Path) -> Vec<OsString> {
+        folder_names(&self.input, base_path)
+    }
+    pub fn complete(&mut self, base_folder: &Folder, base_path: &Path) {
+        // the last name is completed from the contents of the folder before it
+        let (folder_input, partial_name) = match self.input.rfind('/') {
+            Some(index) => self.input.split_at(index + 1),
+            None => ("", self.input.as_str()),
+        };
+        let mut folder = base_folder;
+        for name in folder_names(folder_input, base_path) {
+            folder = match folder.contents.get(&name) {
+                Some(FileOrFolder::Folder(subfolder)) => subfolder,
+                _ => return,
+            };
+        }
+        let mut completions: Vec<String> = folder
+            .contents
+            .iter()
+            .filter_map(|(name, file_or_folder)| {
+                let name = name.to_str()?;
+                if !name.starts_with(partial_name) {
+                    return None;
+                }
+                match file_or_folder {
+                    FileOrFolder::Folder(_) => Some(format!("{}/", name)),
+                    FileOrFolder::File(_) => Some(name.to_string()),
+                }
+            })
+            .collect();
+        completions.sort();
+        let common_prefix = match completions.split_first() {
+            Some((first, rest)) => rest.iter().fold(first.as_str(), |prefix, completion| {
+                common_prefix(prefix, completion)
+            }),
+            None => return,
+        };
+        self.input = format!("{}{}", folder_input, common_prefix);
+        self.completions = if completions.len() > 1 {
+            completions
+        } else {
+            vec![]
+        };
+        self.not_found = false;
+    }
+}
+
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .take_while(|((_, a_char), b_char)| a_char == b_char)
+        .last()
+        .map(|((index, a_char), _)| index + a_char.len_utf8())
+        .unwrap_or(0);
+    &a[..len]
+}
+
+fn folder_names(input: &str, base_path: &Path) -> Vec<OsString> {
+    // absolute paths are accepted as long as they are in the scanned folder
+    let path = Path::new(input);
+    let path = path.strip_prefix(base_path).unwrap_or(path);
+    let mut names = vec![];
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => names.push(name.to_os_string()),
+            Component::ParentDir => {
+                names.pop();
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::files::File;
+
+    fn folder(name: &str, contents: Vec<FileOrFolder>) -> Folder {
+        let mut folder = Folder::from(OsString::from(name));
+        for file_or_folder in contents {
+            let name = match &file_or_folder {
+                FileOrFolder::Folder(folder) => folder.name.clone(),
+                FileOrFolder::File(file) => file.name.clone(),
+            };
+            folder.contents.insert(name, file_or_folder);
+        }
+        folder
+    }
+
+    fn file(name: &str) -> FileOrFolder {
+        FileOrFolder::File(File {
+            name: OsString::from(name),
+            size: 0,
+            apparent_size: 0,
+            disk_size: 0,
+            growth: None,
+            is_hard_linked: false,
+            times: None,
+            owner: None,
+        })
+    }
+
+    #[test]
+    fn parse_relative_and_absolute_paths() {
+        let base_path = Path::new("/home/user");
+        let names = |input: &str| -> Vec<String> {
+            folder_names(input, base_path)
+                .iter()
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names("var/lib/"), vec!["var", "lib"]);
+        assert_eq!(names("./var/../lib"), vec!["lib"]);
+        assert_eq!(names("/home/user/var"), vec!["var"]);
+        assert!(names("").is_empty());
+    }
+
+    #[test]
+    fn complete_names_in_folder() {
+        let base_folder = folder(
+            "base",
+            vec![FileOrFolder::Folder(folder(
+                "var",
+                vec![
+                    FileOrFolder::Folder(folder("lib", vec![])),
+                    FileOrFolder::Folder(folder("log", vec![])),
+                    file("lock"),
+                ],
+            ))],
+        );
+        let base_path = Path::new("/");
+        let mut path_prompt = PathPrompt::new();
+        path_prompt.input = String::from("v");
+        path_prompt.complete(&base_folder, base_path);
+        assert_eq!(path_prompt.input, "var/");
+        assert!(path_prompt.completions.is_empty());
+
+        path_prompt.complete(&base_folder, base_path);
+        assert_eq!(path_prompt.input, "var/l");
+        assert_eq!(path_prompt.completions, vec!["lib/", "lock", "log/"]);
+
+        path_prompt.push('o');
+        path_prompt.complete(&base_folder, base_path);
+        assert_eq!(path_prompt.input, "var/lo");
+        assert_eq!(path_prompt.completions, vec!["lock", "log/"]);
+
+        path_prompt.push('g');
+        path_prompt.complete(&base_folder, base_path);
+        assert_eq!(path_prompt.input, "var/log/");
+    }
+}
@@ -0,0 +1,171 @@
+use ::std::ffi::{OsStr, OsString};
+
+use ::regex::Regex;
+
+use crate::state::files::{FileOrFolder, Folder};
+use crate::state::tiles::FileType;
+
+// the largest matches are the ones worth jumping to, and there is only so
+// much room on screen
+const MAX_SEARCH_RESULTS: usize = 1000;
+
+// matches file and folder names (not their paths) to what was searched for
+#[derive(Clone, Debug)]
+pub enum NameFilter {
+    // case insensitive
+    Substring(String),
+    Regex(Regex),
+}
+
+impl NameFilter {
+    pub fn new(query: &str, is_regex: bool) -> Result<Self, regex::Error> {
+        if is_regex {
+            Ok(NameFilter::Regex(Regex::new(query)?))
+        } else {
+            Ok(NameFilter::Substring(query.to_lowercase()))
+        }
+    }
+    pub fn is_match(&self, name: &OsStr) -> bool {
+        let name = name.to_string_lossy();
+        match self {
+            NameFilter::Substring(substring) => name.to_lowercase().contains(substring.as_str()),
+            NameFilter::Regex(regex) => regex.is_match(&name),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub path_to_file: Vec<OsString>,
+    pub size: u128,
+    pub file_type: FileType,
+}
+
+#[derive(Clone, Debug)]
+pub struct Search {
+    pub query: String,
+    pub is_regex: bool,
+    // search the whole file tree rather than only the current folder
+    pub is_recursive: bool,
+    // None if the query is not a valid regex
+    pub name_filter: Option<NameFilter>,
+    // the matches from anywhere in the file tree, largest first
+    pub results: Vec<SearchResult>,
+    pub selected_index: usize,
+    // bumped whenever the query or the kind of search changes, so that
+    // results are only found for the latest one
+    pub changes: u64,
+    // the results are still those of an earlier query
+    pub results_pending: bool,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Search {
+            query: String::new(),
+            is_regex: false,
+            is_recursive: false,
+            name_filter: None,
+            results: vec![],
+            selected_index: 0,
+            changes: 0,
+            results_pending: false,
+        }
+    }
+    pub fn update(&mut self) {
+        // called whenever the query or the kind of search changes, searching
+        // everywhere is left for find_results so it does not run on every keystroke
+        self.name_filter = if self.query.is_empty() {
+            None
+        } else {
+            NameFilter::new(&self.query, self.is_regex).ok()
+        };
+        self.changes += 1;
+        self.results_pending = self.is_recursive && self.name_filter.is_some();
+        if !self.results_pending {
+            self.results = vec![];
+        }
+        self.selected_index = 0;
+    }
+    pub fn find_results(&mut self, base_folder: &Folder) {
+        if let (true, Some(name_filter)) = (self.results_pending, &self.name_filter) {
+            self.results = search_folder(base_folder, name_filter);
+            self.results_pending = false;
+            self.selected_index = 0;
+        }
+    }
+    pub fn is_invalid(&self) -> bool {
+        !self.query.is_empty() && self.name_filter.is_none()
+    }
+    pub fn selected_result(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected_index)
+    }
+    pub fn select_next(&mut self) {
+        if self.selected_index + 1 < self.results.len() {
+            self.selected_index += 1;
+        }
+    }
+    pub fn select_previous(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+}
+
+fn add_matches(
+    folder: &Folder,
+    folder_names: &mut Vec<OsString>,
+    name_filter: &NameFilter,
+    results: &mut Vec<SearchResult>,
+) {
+    for (name, file_or_folder) in &folder.contents {
+        if name_filter.is_match(name) {
+            let mut path_to_file = folder_names.clone();
+            path_to_file.push(name.clone());
+            let file_type = match file_or_folder {
+                FileOrFolder::Folder(_) => FileType::Folder,
+                FileOrFolder::File(_) => FileType::File,
+            };
+            results.push(SearchResult {
+                path_to_file,
+                size: file_or_folder.size(),
+                file_type,
+            });
+        }
+        if let FileOrFolder::Folder(subfolder) = file_or_folder {
+            folder_names.push(name.clone());
+            add_matches(subfolder, folder_names, name_filter, results);
+            folder_names.pop();
+        }
+    }
+}
+
+pub fn search_folder(folder: &Folder, name_filter: &NameFilter) -> Vec<SearchResult> {
+    let mut results = vec![];
+    add_matches(folder, &mut vec![], name_filter, &mut results);
+    results.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then_with(|| a.path_to_file.cmp(&b.path_to_file))
+    });
+    results.truncate(MAX_SEARCH_RESULTS);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_names_by_substring_or_regex() {
+        let substring = NameFilter::new("READ", false).expect("failed to create filter");
+        assert!(substring.is_match(OsStr::new("README.md")));
+        assert!(substring.is_match(OsStr::new("unread")));
+        assert!(!substring.is_match(OsStr::new("main.rs")));
+
+        let regex = NameFilter::new(r"\.rs$", true).expect("failed to create filter");
+        assert!(regex.is_match(OsStr::new("main.rs")));
+        assert!(!regex.is_match(OsStr::new("main.rs.bak")));
+
+        assert!(NameFilter::new("(", true).is_err());
+        assert!(NameFilter::new("(", false).is_ok());
+    }
+}
@@ -1,8 +1,25 @@
+use ::std::collections::HashSet;
+use ::std::ffi::OsString;
+
 use ::tui::layout::Rect;
 
 use crate::state::files::Folder;
+use crate::state::search::NameFilter;
 use crate::state::tiles::files_in_folder::FileType;
-use crate::state::tiles::{files_in_folder, FileMetadata, Tile, TreeMap};
+use crate::state::tiles::{
+    arrange_files, files_in_folder, files_in_view, FileMetadata, OwnerFilter, Tile, TreeMap, View,
+};
+
+// the files of a view, before they are filtered by name or zoomed into, and what they
+// were found in. most views walk everything in the folder to find them, so they are
+// only found again once the folder, the view or the owner we filter by change
+struct ViewFiles {
+    folder_names: Vec<OsString>,
+    folder_changes: u64,
+    view: View,
+    owner_filter: Option<OwnerFilter>,
+    files: Vec<FileMetadata>,
+}
 
 pub struct Board {
     pub tiles: Vec<Tile>,
@@ -12,6 +29,19 @@ pub struct Board {
     pub zoom_level: usize,
     area: Rect,
     files: Vec<FileMetadata>,
+    view_files: Option<ViewFiles>,
+    // names of the marked files and folders in the current folder
+    marked_names: HashSet<OsString>,
+    // names of the protected files and folders in the current folder
+    protected_names: HashSet<OsString>,
+    // only the files matching the search are shown
+    name_filter: Option<NameFilter>,
+    // only the files of this user or group are shown
+    owner_filter: Option<OwnerFilter>,
+    pub view: View,
+    // files and folders last touched before this (in seconds since the
+    // unix epoch) are stale (see --stale-after)
+    stale_before: Option<u64>,
 }
 
 impl Board {
@@ -19,10 +49,17 @@ impl Board {
         Board {
             tiles: vec![],
             unrenderable_tile_coordinates: None,
-            files: files_in_folder(folder, 0),
+            files: files_in_folder(folder, 0, None, None, &View::Folder),
+            view_files: None,
             selected_index: None,
             previous_indices_and_zoom_level: vec![],
             zoom_level: 0,
+            marked_names: HashSet::new(),
+            protected_names: HashSet::new(),
+            name_filter: None,
+            owner_filter: None,
+            view: View::Folder,
+            stale_before: None,
             area: Rect {
                 x: 0,
                 y: 0,
@@ -31,8 +68,43 @@ impl Board {
             },
         }
     }
-    pub fn change_files(&mut self, folder: &Folder) {
-        self.files = files_in_folder(folder, self.zoom_level);
+    pub fn change_files(
+        &mut self,
+        folder: &Folder,
+        folder_names: &[OsString],
+        folder_changes: u64,
+    ) {
+        // folder_changes goes up whenever anything in the file tree changes
+        let is_stale = match &self.view_files {
+            Some(view_files) => {
+                view_files.folder_names[..] != *folder_names
+                    || view_files.folder_changes != folder_changes
+                    || view_files.view != self.view
+                    || view_files.owner_filter != self.owner_filter
+            }
+            None => true,
+        };
+        if is_stale {
+            self.view_files = Some(ViewFiles {
+                folder_names: folder_names.to_vec(),
+                folder_changes,
+                view: self.view.clone(),
+                owner_filter: self.owner_filter.clone(),
+                files: files_in_view(folder, self.owner_filter.as_ref(), &self.view),
+            });
+        }
+        let view_files = self
+            .view_files
+            .as_ref()
+            .expect("view files were just found");
+        self.files = arrange_files(
+            view_files.files.clone(),
+            folder.size,
+            self.zoom_level,
+            self.name_filter.as_ref(),
+            self.owner_filter.as_ref(),
+            &self.view,
+        );
         self.fill();
     }
     pub fn change_area(&mut self, area: &Rect) {
@@ -41,10 +113,48 @@ impl Board {
             self.fill();
         }
     }
+    pub fn change_marked_names(&mut self, marked_names: HashSet<OsString>) {
+        self.marked_names = marked_names;
+    }
+    pub fn change_protected_names(&mut self, protected_names: HashSet<OsString>) {
+        self.protected_names = protected_names;
+        for tile in self.tiles.iter_mut() {
+            tile.is_protected = self.protected_names.contains(&tile.name);
+        }
+    }
+    pub fn change_name_filter(&mut self, name_filter: Option<NameFilter>) {
+        self.name_filter = name_filter;
+    }
+    pub fn change_owner_filter(&mut self, owner_filter: Option<OwnerFilter>) {
+        self.owner_filter = owner_filter;
+    }
+    pub fn owner_filter(&self) -> Option<&OwnerFilter> {
+        self.owner_filter.as_ref()
+    }
+    pub fn is_filtered(&self) -> bool {
+        self.name_filter.is_some()
+    }
+    pub fn change_stale_before(&mut self, stale_before: Option<u64>) {
+        self.stale_before = stale_before;
+    }
+    pub fn change_view(&mut self, view: View) {
+        self.view = view;
+    }
+    pub fn file_names(&self) -> Vec<&OsString> {
+        self.files.iter().map(|file| &file.name).collect()
+    }
     fn fill(&mut self) {
         let mut tree_map = TreeMap::new(&self.area);
         tree_map.populate_tiles(self.files.iter().collect());
         self.tiles = tree_map.tiles;
+        for tile in self.tiles.iter_mut() {
+            tile.is_marked = self.marked_names.contains(&tile.name);
+            tile.is_protected = self.protected_names.contains(&tile.name);
+            tile.is_stale = matches!(
+                (self.stale_before, tile.last_touched),
+                (Some(stale_before), Some(last_touched)) if last_touched < stale_before
+            );
+        }
         self.unrenderable_tile_coordinates = tree_map.unrenderable_tile_coordinates;
     }
     pub fn get_selected_index(&self) -> Option<usize> {
@@ -68,6 +178,26 @@ impl Board {
     pub fn pop_previous_index_and_zoom_level(&mut self) -> Option<(Option<usize>, usize)> {
         self.previous_indices_and_zoom_level.pop()
     }
+    pub fn tile_index(&self, folder: &Folder, name: &OsString) -> Option<usize> {
+        // the index the tile of name would have if folder were shown (and not zoomed in)
+        let files = files_in_folder(folder, 0, None, self.owner_filter.as_ref(), &View::Folder);
+        let mut tree_map = TreeMap::new(&self.area);
+        tree_map.populate_tiles(files.iter().collect());
+        tree_map.tiles.iter().position(|tile| &tile.name == name)
+    }
+    pub fn select_name(&mut self, name: &OsString, folder: &Folder) -> bool {
+        // zooms in until the file is large enough to have a tile of its own
+        loop {
+            if let Some(index) = self.tiles.iter().position(|tile| &tile.name == name) {
+                self.set_selected_index(&index);
+                return true;
+            }
+            if self.zoom_level >= self.files.len() {
+                return false;
+            }
+            self.zoom_in(folder);
+        }
+    }
     pub fn move_to_largest_folder(&mut self) {
         let next_index = self
             .tiles
@@ -172,20 +302,38 @@ impl Board {
     pub fn zoom_in(&mut self, folder: &Folder) {
         if self.zoom_level < self.files.len() {
             self.zoom_level += 1;
-            self.files = files_in_folder(folder, self.zoom_level);
+            self.files = files_in_folder(
+                folder,
+                self.zoom_level,
+                self.name_filter.as_ref(),
+                self.owner_filter.as_ref(),
+                &self.view,
+            );
             self.fill();
         }
     }
     pub fn zoom_out(&mut self, folder: &Folder) {
         if self.zoom_level > 0 {
             self.zoom_level -= 1;
-            self.files = files_in_folder(folder, self.zoom_level);
+            self.files = files_in_folder(
+                folder,
+                self.zoom_level,
+                self.name_filter.as_ref(),
+                self.owner_filter.as_ref(),
+                &self.view,
+            );
             self.fill();
         }
     }
     pub fn reset_zoom(&mut self, folder: &Folder) {
         self.zoom_level = 0;
-        self.files = files_in_folder(folder, self.zoom_level);
+        self.files = files_in_folder(
+            folder,
+            self.zoom_level,
+            self.name_filter.as_ref(),
+            self.owner_filter.as_ref(),
+            &self.view,
+        );
         self.fill();
     }
     pub fn reset_zoom_index(&mut self) {
@@ -199,3 +347,28 @@ impl Board {
             .push((self.get_selected_index(), self.zoom_level));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::path::PathBuf;
+
+    #[test]
+    fn find_view_files_again_only_once_the_folder_changes() {
+        let mut folder = Folder::new(&PathBuf::from("/tmp/folder"));
+        folder.add_file(PathBuf::from("subfolder/file1"), 4096);
+        let mut board = Board::new(&folder);
+        board.change_view(View::LargestFiles);
+        board.change_files(&folder, &[], 0);
+        assert_eq!(board.file_names(), vec!["subfolder/file1"]);
+
+        folder.add_file(PathBuf::from("subfolder/file2"), 8192);
+        board.change_files(&folder, &[], 0);
+        assert_eq!(board.file_names(), vec!["subfolder/file1"]);
+        board.change_files(&folder, &[], 1);
+        assert_eq!(
+            board.file_names(),
+            vec!["subfolder/file2", "subfolder/file1"]
+        );
+    }
+}
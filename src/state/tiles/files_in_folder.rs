@@ -1,6 +1,14 @@
-use ::std::ffi::OsString;
+use ::std::collections::HashMap;
+use ::std::ffi::{OsStr, OsString};
+use ::std::path::{Path, PathBuf};
 
-use crate::state::files::{FileOrFolder, Folder};
+use crate::state::files::{File, FileOrFolder, FileOwner, Folder};
+use crate::state::search::NameFilter;
+
+#[cfg(not(target_os = "windows"))]
+use crate::os::unix::{group_name, user_name};
+#[cfg(target_os = "windows")]
+use crate::os::windows::{group_name, user_name};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum FileType {
@@ -8,13 +16,146 @@ pub enum FileType {
     Folder,
 }
 
+// what the tiles of a folder stand for
+#[derive(Clone, Debug, PartialEq)]
+pub enum View {
+    // the files and folders in it
+    Folder,
+    // everything under it, grouped by the kind of file it is
+    Categories,
+    // the files of this kind anywhere under it, by their path in the folder
+    Category(OsString),
+    // the largest files anywhere under it, by their path in the folder
+    LargestFiles,
+    // everything under it, grouped by the user or group it belongs to
+    Owners(OwnerKind),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OwnerKind {
+    User,
+    Group,
+}
+
+impl OwnerKind {
+    fn id(self, owner: FileOwner) -> u32 {
+        match self {
+            OwnerKind::User => owner.uid,
+            OwnerKind::Group => owner.gid,
+        }
+    }
+    fn name(self, id: Option<u32>) -> OsString {
+        // ids without an entry in the passwd / group database are shown as is
+        let id = match id {
+            Some(id) => id,
+            None => return OsString::from("Unknown owner"),
+        };
+        let name = match self {
+            OwnerKind::User => user_name(id),
+            OwnerKind::Group => group_name(id),
+        };
+        OsString::from(name.unwrap_or_else(|| id.to_string()))
+    }
+}
+
+// only the files of this user or group are counted
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnerFilter {
+    pub kind: OwnerKind,
+    // None for files we do not know the owner of
+    pub id: Option<u32>,
+    pub name: OsString,
+}
+
+impl OwnerFilter {
+    fn is_match(&self, file: &File) -> bool {
+        file.owner.map(|owner| self.kind.id(owner)) == self.id
+    }
+}
+
+// there is hardly room for more tiles than this on the screen
+const LARGEST_FILES: usize = 100;
+
+// files whose extension is not listed here are grouped by their extension
+const CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "Videos",
+        &[
+            "avi", "flv", "m4v", "mkv", "mov", "mp4", "mpeg", "mpg", "webm", "wmv",
+        ],
+    ),
+    (
+        "Audio",
+        &["aac", "flac", "m4a", "mp3", "ogg", "opus", "wav", "wma"],
+    ),
+    (
+        "Images",
+        &[
+            "bmp", "gif", "heic", "ico", "jpeg", "jpg", "png", "psd", "raw", "svg", "tif", "tiff",
+            "webp",
+        ],
+    ),
+    (
+        "Documents",
+        &[
+            "doc", "docx", "epub", "md", "odp", "ods", "odt", "pdf", "ppt", "pptx", "rtf", "txt",
+            "xls", "xlsx",
+        ],
+    ),
+    (
+        "Archives",
+        &[
+            "7z", "bz2", "deb", "dmg", "gz", "iso", "jar", "rar", "rpm", "tar", "tgz", "xz", "zip",
+            "zst",
+        ],
+    ),
+    ("Logs", &["log"]),
+    (
+        "Build artifacts",
+        &[
+            "a", "class", "d", "dll", "exe", "lib", "o", "obj", "pyc", "rlib", "rmeta", "so",
+            "wasm",
+        ],
+    ),
+    (
+        "Code",
+        &[
+            "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "html", "java", "js", "json", "kt",
+            "py", "rb", "rs", "sh", "swift", "toml", "ts", "xml", "yaml", "yml",
+        ],
+    ),
+    ("Databases", &["db", "sqlite", "sqlite3"]),
+];
+
+pub fn file_category(file_name: &OsStr) -> OsString {
+    let extension = match Path::new(file_name).extension() {
+        Some(extension) => extension.to_string_lossy().to_lowercase(),
+        None => return OsString::from("No extension"),
+    };
+    let category = CATEGORIES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension.as_str()));
+    match category {
+        Some((category, _)) => OsString::from(category),
+        None => OsString::from(format!(".{}", extension)),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileMetadata {
     pub name: OsString,
     pub size: u128,
+    pub apparent_size: u128,
+    pub disk_size: u128,
     pub descendants: Option<u64>,
     pub percentage: f64, // 1.0 is 100% (0.5 is 50%, etc.)
     pub file_type: FileType,
+    pub growth: Option<i128>,
+    pub is_mount_point: bool,
+    pub is_hard_linked: bool,
+    // when it (or anything in it) was last modified or accessed, in
+    // seconds since the unix epoch
+    pub last_touched: Option<u64>,
 }
 
 fn calculate_percentage(size: u128, total_size: u128, total_files_in_parent: usize) -> f64 {
@@ -27,26 +168,268 @@ fn calculate_percentage(size: u128, total_size: u128, total_files_in_parent: usi
     }
 }
 
-pub fn files_in_folder(folder: &Folder, offset: usize) -> Vec<FileMetadata> {
-    let mut files = Vec::new();
-    let total_size = folder.size;
+fn add_growth(growth: Option<i128>, file_growth: Option<i128>) -> Option<i128> {
+    match (growth, file_growth) {
+        (None, None) => None,
+        (growth, file_growth) => Some(growth.unwrap_or(0) + file_growth.unwrap_or(0)),
+    }
+}
+
+fn for_each_file(
+    folder: &Folder,
+    path: &mut PathBuf,
+    owner_filter: Option<&OwnerFilter>,
+    f: &mut impl FnMut(&Path, &File),
+) {
     for (name, file_or_folder) in &folder.contents {
-        files.push({
-            let size = file_or_folder.size();
-            let name = name.clone();
-            let (descendants, file_type) = match file_or_folder {
-                FileOrFolder::Folder(folder) => (Some(folder.num_descendants), FileType::Folder),
-                FileOrFolder::File(_file) => (None, FileType::File),
+        path.push(name);
+        match file_or_folder {
+            FileOrFolder::Folder(subfolder) => for_each_file(subfolder, path, owner_filter, f),
+            FileOrFolder::File(file) => match owner_filter {
+                Some(owner_filter) if !owner_filter.is_match(file) => {}
+                _ => f(path, file),
+            },
+        }
+        path.pop();
+    }
+}
+
+fn group_of_files(name: OsString) -> FileMetadata {
+    // a group of files is shown like a folder, since it can be entered
+    FileMetadata {
+        name,
+        size: 0,
+        apparent_size: 0,
+        disk_size: 0,
+        descendants: Some(0),
+        percentage: 0.0,
+        file_type: FileType::Folder,
+        growth: None,
+        is_mount_point: false,
+        is_hard_linked: false,
+        last_touched: None,
+    }
+}
+
+fn add_to_group(group: &mut FileMetadata, file: &File) {
+    group.size += file.size;
+    group.apparent_size += file.apparent_size;
+    group.disk_size += file.disk_size;
+    group.last_touched = group
+        .last_touched
+        .max(file.times.map(|times| times.last_touched()));
+    group.descendants = group.descendants.map(|count| count + 1);
+    group.growth = add_growth(group.growth, file.growth);
+    group.is_hard_linked |= file.is_hard_linked;
+}
+
+fn folder_contents(folder: &Folder, owner_filter: Option<&OwnerFilter>) -> Vec<FileMetadata> {
+    match owner_filter {
+        Some(owner_filter) => owned_folder_contents(folder, owner_filter),
+        None => all_folder_contents(folder),
+    }
+}
+
+fn owned_folder_contents(folder: &Folder, owner_filter: &OwnerFilter) -> Vec<FileMetadata> {
+    // folders are as large as the files of the owner in them, and left
+    // out if there are none
+    folder
+        .contents
+        .iter()
+        .filter_map(|(name, file_or_folder)| match file_or_folder {
+            FileOrFolder::Folder(subfolder) => {
+                let mut files = group_of_files(name.clone());
+                for_each_file(
+                    subfolder,
+                    &mut PathBuf::new(),
+                    Some(owner_filter),
+                    &mut |_, file| add_to_group(&mut files, file),
+                );
+                match files.descendants {
+                    Some(0) => None,
+                    _ => Some(files),
+                }
+            }
+            FileOrFolder::File(file) if owner_filter.is_match(file) => {
+                Some(file_at_path(Path::new(name), file))
+            }
+            FileOrFolder::File(_) => None,
+        })
+        .collect()
+}
+
+fn all_folder_contents(folder: &Folder) -> Vec<FileMetadata> {
+    folder
+        .contents
+        .iter()
+        .map(|(name, file_or_folder)| {
+            let (descendants, file_type, is_mount_point) = match file_or_folder {
+                FileOrFolder::Folder(folder) => (
+                    Some(folder.num_descendants),
+                    FileType::Folder,
+                    folder.is_mount_point,
+                ),
+                FileOrFolder::File(_file) => (None, FileType::File, false),
             };
-            let percentage = calculate_percentage(size, total_size, folder.contents.len());
             FileMetadata {
-                size,
-                name,
+                size: file_or_folder.size(),
+                apparent_size: file_or_folder.apparent_size(),
+                disk_size: file_or_folder.disk_size(),
+                name: name.clone(),
                 descendants,
-                percentage,
+                percentage: 0.0,
                 file_type,
+                growth: file_or_folder.growth(),
+                is_mount_point,
+                is_hard_linked: file_or_folder.is_hard_linked(),
+                last_touched: file_or_folder.last_touched(),
             }
-        });
+        })
+        .collect()
+}
+
+fn categories(folder: &Folder, owner_filter: Option<&OwnerFilter>) -> Vec<FileMetadata> {
+    let mut categories: HashMap<OsString, FileMetadata> = HashMap::new();
+    for_each_file(folder, &mut PathBuf::new(), owner_filter, &mut |_, file| {
+        let name = file_category(&file.name);
+        let category = categories
+            .entry(name.clone())
+            .or_insert_with(|| group_of_files(name));
+        add_to_group(category, file);
+    });
+    categories.into_values().collect()
+}
+
+fn owners(folder: &Folder, kind: OwnerKind) -> HashMap<Option<u32>, FileMetadata> {
+    let mut owners: HashMap<Option<u32>, FileMetadata> = HashMap::new();
+    for_each_file(folder, &mut PathBuf::new(), None, &mut |_, file| {
+        let id = file.owner.map(|owner| kind.id(owner));
+        let owner = owners
+            .entry(id)
+            .or_insert_with(|| group_of_files(kind.name(id)));
+        add_to_group(owner, file);
+    });
+    owners
+}
+
+pub fn find_owner(folder: &Folder, kind: OwnerKind, name: &OsStr) -> Option<OwnerFilter> {
+    // the owner a tile of the owners view stands for
+    owners(folder, kind)
+        .into_iter()
+        .find(|(_, owner)| owner.name == name)
+        .map(|(id, owner)| OwnerFilter {
+            kind,
+            id,
+            name: owner.name,
+        })
+}
+
+fn file_at_path(path: &Path, file: &File) -> FileMetadata {
+    FileMetadata {
+        name: path.as_os_str().to_os_string(),
+        size: file.size,
+        apparent_size: file.apparent_size,
+        disk_size: file.disk_size,
+        descendants: None,
+        percentage: 0.0,
+        file_type: FileType::File,
+        growth: file.growth,
+        is_mount_point: false,
+        is_hard_linked: file.is_hard_linked,
+        last_touched: file.times.map(|times| times.last_touched()),
+    }
+}
+
+fn files_in_category(
+    folder: &Folder,
+    category: &OsStr,
+    owner_filter: Option<&OwnerFilter>,
+) -> Vec<FileMetadata> {
+    let mut files = vec![];
+    for_each_file(
+        folder,
+        &mut PathBuf::new(),
+        owner_filter,
+        &mut |path, file| {
+            if file_category(&file.name) == category {
+                files.push(file_at_path(path, file));
+            }
+        },
+    );
+    files
+}
+
+fn largest_files(folder: &Folder, owner_filter: Option<&OwnerFilter>) -> Vec<FileMetadata> {
+    let mut files = vec![];
+    for_each_file(
+        folder,
+        &mut PathBuf::new(),
+        owner_filter,
+        &mut |path, file| {
+            files.push(file_at_path(path, file));
+        },
+    );
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    files.truncate(LARGEST_FILES);
+    files
+}
+
+pub fn files_in_view(
+    folder: &Folder,
+    owner_filter: Option<&OwnerFilter>,
+    view: &View,
+) -> Vec<FileMetadata> {
+    // other than the folder's own contents, these walk everything in the folder
+    match view {
+        View::Folder => folder_contents(folder, owner_filter),
+        View::Categories => categories(folder, owner_filter),
+        View::Category(category) => files_in_category(folder, category, owner_filter),
+        View::LargestFiles => largest_files(folder, owner_filter),
+        // the owners are always shown in full, whichever one we filter by
+        View::Owners(kind) => owners(folder, *kind).into_values().collect(),
+    }
+}
+
+pub fn files_in_folder(
+    folder: &Folder,
+    offset: usize,
+    name_filter: Option<&NameFilter>,
+    owner_filter: Option<&OwnerFilter>,
+    view: &View,
+) -> Vec<FileMetadata> {
+    arrange_files(
+        files_in_view(folder, owner_filter, view),
+        folder.size,
+        offset,
+        name_filter,
+        owner_filter,
+        view,
+    )
+}
+
+pub fn arrange_files(
+    files: Vec<FileMetadata>,
+    folder_size: u128,
+    offset: usize,
+    name_filter: Option<&NameFilter>,
+    owner_filter: Option<&OwnerFilter>,
+    view: &View,
+) -> Vec<FileMetadata> {
+    // when searching, the matching files fill the whole folder
+    let mut files: Vec<FileMetadata> = files
+        .into_iter()
+        .filter(|file| match name_filter {
+            Some(name_filter) => name_filter.is_match(&file.name),
+            None => true,
+        })
+        .collect();
+    let total_size = match (view, name_filter, owner_filter) {
+        (View::Folder, None, None) => folder_size,
+        _ => files.iter().map(|file| file.size).sum(),
+    };
+    let total_files = files.len();
+    for file in files.iter_mut() {
+        file.percentage = calculate_percentage(file.size, total_size, total_files);
     }
     files.sort_by(|a, b| {
         if a.percentage == b.percentage {
@@ -59,7 +442,7 @@ pub fn files_in_folder(folder: &Folder, offset: usize) -> Vec<FileMetadata> {
     });
     if offset > 0 {
         let removed_items = files.drain(..offset);
-        let number_of_files_without_removed_contents = folder.contents.len() - removed_items.len();
+        let number_of_files_without_removed_contents = total_files - removed_items.len();
         let removed_size = removed_items.fold(0, |acc, file| acc + file.size);
         let size_without_removed_items = total_size - removed_size;
         for i in 0..files.len() {
@@ -72,3 +455,165 @@ pub fn files_in_folder(folder: &Folder, offset: usize) -> Vec<FileMetadata> {
     }
     files
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_file(folder: &mut Folder, name: &str, size: u128) {
+        let file = File {
+            name: OsString::from(name),
+            size,
+            apparent_size: size,
+            disk_size: size,
+            growth: None,
+            is_hard_linked: false,
+            times: None,
+            owner: None,
+        };
+        folder.size += size;
+        folder.num_descendants += 1;
+        folder
+            .contents
+            .insert(OsString::from(name), FileOrFolder::File(file));
+    }
+
+    fn folder_with_subfolder() -> Folder {
+        let mut subfolder = Folder::from(OsString::from("subfolder"));
+        add_file(&mut subfolder, "movie.MKV", 4096);
+        add_file(&mut subfolder, "data.xyz", 1024);
+        let mut folder = Folder::from(OsString::from("folder"));
+        add_file(&mut folder, "clip.mp4", 2048);
+        add_file(&mut folder, "Makefile", 512);
+        folder.size += subfolder.size;
+        folder.num_descendants += subfolder.num_descendants + 1;
+        folder
+            .contents
+            .insert(OsString::from("subfolder"), FileOrFolder::Folder(subfolder));
+        folder
+    }
+
+    #[test]
+    fn group_files_by_category() {
+        let folder = folder_with_subfolder();
+
+        let categories = files_in_folder(&folder, 0, None, None, &View::Categories);
+        let categories: Vec<(String, u128, Option<u64>)> = categories
+            .iter()
+            .map(|category| {
+                (
+                    category.name.to_string_lossy().into_owned(),
+                    category.size,
+                    category.descendants,
+                )
+            })
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                (String::from("Videos"), 6144, Some(2)),
+                (String::from(".xyz"), 1024, Some(1)),
+                (String::from("No extension"), 512, Some(1)),
+            ]
+        );
+
+        let videos = files_in_folder(
+            &folder,
+            0,
+            None,
+            None,
+            &View::Category(OsString::from("Videos")),
+        );
+        let videos: Vec<String> = videos
+            .iter()
+            .map(|file| file.name.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(videos, vec!["subfolder/movie.MKV", "clip.mp4"]);
+    }
+
+    #[test]
+    fn list_largest_files() {
+        let folder = folder_with_subfolder();
+        let files = files_in_folder(&folder, 0, None, None, &View::LargestFiles);
+        let files: Vec<(String, u128)> = files
+            .iter()
+            .map(|file| (file.name.to_string_lossy().into_owned(), file.size))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                (String::from("subfolder/movie.MKV"), 4096),
+                (String::from("clip.mp4"), 2048),
+                (String::from("subfolder/data.xyz"), 1024),
+                (String::from("Makefile"), 512),
+            ]
+        );
+    }
+
+    fn set_owners(folder: &mut Folder, uid_of: &impl Fn(&OsStr) -> u32) {
+        for file_or_folder in folder.contents.values_mut() {
+            match file_or_folder {
+                FileOrFolder::Folder(subfolder) => set_owners(subfolder, uid_of),
+                FileOrFolder::File(file) => {
+                    file.owner = Some(FileOwner {
+                        uid: uid_of(&file.name),
+                        gid: 54320,
+                    })
+                }
+            }
+        }
+    }
+
+    fn summary(files: &[FileMetadata]) -> Vec<(String, u128, Option<u64>)> {
+        files
+            .iter()
+            .map(|file| {
+                (
+                    file.name.to_string_lossy().into_owned(),
+                    file.size,
+                    file.descendants,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn group_and_filter_files_by_owner() {
+        // ids that are not in the passwd / group database are shown as they are
+        let mut folder = folder_with_subfolder();
+        set_owners(&mut folder, &|name| {
+            if Path::new(name).extension() == Some(OsStr::new("xyz")) || name == "Makefile" {
+                54322
+            } else {
+                54321
+            }
+        });
+
+        let users = files_in_folder(&folder, 0, None, None, &View::Owners(OwnerKind::User));
+        assert_eq!(
+            summary(&users),
+            vec![
+                (String::from("54321"), 6144, Some(2)),
+                (String::from("54322"), 1536, Some(2)),
+            ]
+        );
+        let groups = files_in_folder(&folder, 0, None, None, &View::Owners(OwnerKind::Group));
+        assert_eq!(
+            summary(&groups),
+            vec![(String::from("54320"), 7680, Some(4))]
+        );
+
+        let owner_filter = find_owner(&folder, OwnerKind::User, OsStr::new("54322"))
+            .expect("could not find owner");
+        assert_eq!(owner_filter.id, Some(54322));
+        let owned = files_in_folder(&folder, 0, None, Some(&owner_filter), &View::Folder);
+        assert_eq!(
+            summary(&owned),
+            vec![
+                (String::from("subfolder"), 1024, Some(1)),
+                (String::from("Makefile"), 512, None),
+            ]
+        );
+        assert!(find_owner(&folder, OwnerKind::User, OsStr::new("nobody-here")).is_none());
+    }
+}
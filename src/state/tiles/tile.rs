@@ -10,9 +10,22 @@ pub struct Tile {
     pub height: u16,
     pub name: OsString,
     pub size: u128,
+    pub apparent_size: u128,
+    pub disk_size: u128,
     pub descendants: Option<u64>,
     pub percentage: f64,
     pub file_type: FileType,
+    pub growth: Option<i128>,
+    pub is_mount_point: bool,
+    pub is_hard_linked: bool,
+    // in seconds since the unix epoch
+    pub last_touched: Option<u64>,
+    // marked to be deleted along with other marked tiles
+    pub is_marked: bool,
+    // cannot be deleted (see --protect)
+    pub is_protected: bool,
+    // nothing in it was modified or accessed for a while (see --stale-after)
+    pub is_stale: bool,
 }
 
 impl Tile {
@@ -25,9 +38,18 @@ impl Tile {
             height: rounded.height,
             name: file_metadata.name.clone(),
             size: file_metadata.size,
+            apparent_size: file_metadata.apparent_size,
+            disk_size: file_metadata.disk_size,
             descendants: file_metadata.descendants,
             percentage: file_metadata.percentage,
             file_type: file_metadata.file_type,
+            growth: file_metadata.growth,
+            is_mount_point: file_metadata.is_mount_point,
+            is_hard_linked: file_metadata.is_hard_linked,
+            last_touched: file_metadata.last_touched,
+            is_marked: false,
+            is_protected: false,
+            is_stale: false,
         }
     }
     pub fn is_directly_right_of(&self, other: &Tile) -> bool {
@@ -1,10 +1,26 @@
 use ::std::path::PathBuf;
 
+use crate::deleter::DeletionProgress;
+
 pub struct UiEffects {
     pub flash_space_freed: bool,
     pub current_path_is_red: bool,
     pub deletion_in_progress: bool,
+    // how far along a deletion running in the background is, out of how many files
+    pub deletion_progress: Option<(DeletionProgress, u64)>,
     pub loading_progress_indicator: u64,
+    // a folder is being scanned again after the initial scan was done
+    pub rescanning_folder: bool,
+    // there are deleted files we can restore
+    pub undo_available: bool,
+    // how many files and folders are marked to be deleted, and their total size
+    pub marked: Option<(usize, u128)>,
+    // deletions are only written to a script (see --dry-run)
+    pub dry_run: bool,
+    // nothing can be deleted (see --read-only)
+    pub read_only: bool,
+    // the current folder only shows what matches this search
+    pub search_filter: Option<String>,
     pub last_read_path: Option<PathBuf>,
 }
 
@@ -14,7 +30,14 @@ impl UiEffects {
             flash_space_freed: false,
             current_path_is_red: false,
             deletion_in_progress: false,
+            deletion_progress: None,
             loading_progress_indicator: 0,
+            rescanning_folder: false,
+            undo_available: false,
+            marked: None,
+            dry_run: false,
+            read_only: false,
+            search_filter: None,
             last_read_path: None,
         }
     }
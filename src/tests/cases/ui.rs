@@ -1,17 +1,23 @@
 use ::std::env;
-use ::std::fs::{create_dir, create_dir_all, remove_dir_all, File};
+use ::std::fs::{create_dir, create_dir_all, remove_dir_all, File, Permissions};
 use ::std::io::prelude::*;
 use ::std::iter;
+use ::std::os::unix::fs::PermissionsExt;
 use ::std::path::{Path, PathBuf};
 
 use ::insta::assert_snapshot;
 use crossterm::event::KeyModifiers;
 use crossterm::event::{Event, KeyCode, KeyEvent};
 
-use crate::start;
+use crate::audit_log::AuditLog;
+use crate::dry_run::DryRunScript;
+use crate::protected_paths::ProtectedPaths;
+use crate::scanner::{scan_folder, ScanFilter};
+use crate::state::files::{diff_file_trees, FileTree, Folder};
 use crate::tests::cases::test_utils::*;
 use crate::tests::fakes::TerminalEvent::*;
 use crate::tests::fakes::TerminalEvents;
+use crate::{start, StartOptions};
 
 macro_rules! key {
     (char $x:expr) => {
@@ -86,8 +92,11 @@ fn two_large_files_one_small_file() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -126,8 +135,11 @@ fn medium_width() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -166,8 +178,11 @@ fn small_width() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -207,8 +222,11 @@ fn small_width_long_folder_name() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -248,8 +266,11 @@ fn too_small_width_one() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -288,8 +309,11 @@ fn too_small_width_two() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -316,8 +340,11 @@ fn too_small_width_three() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -344,8 +371,11 @@ fn too_small_width_four() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -372,8 +402,11 @@ fn too_small_width_five() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -400,8 +433,11 @@ fn too_small_height() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -471,8 +507,11 @@ fn eleven_files() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -532,8 +571,11 @@ fn enter_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -601,8 +643,11 @@ fn enter_folder_medium_width() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -671,8 +716,11 @@ fn enter_folder_small_width() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -727,8 +775,11 @@ fn small_files() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -795,8 +846,11 @@ fn zoom_into_small_files() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -893,8 +947,11 @@ fn cannot_move_into_small_files() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -952,8 +1009,11 @@ fn minimum_tile_sides() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -1016,8 +1076,11 @@ fn move_down_and_enter_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1082,8 +1145,11 @@ fn noop_when_entering_file() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1155,8 +1221,11 @@ fn move_up_and_enter_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1228,8 +1297,11 @@ fn move_right_and_enter_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1301,8 +1373,11 @@ fn move_left_and_enter_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1370,8 +1445,11 @@ fn enter_largest_folder_with_no_selected_tile() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1431,8 +1509,11 @@ fn clear_selection_when_moving_off_screen_edges() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1505,8 +1586,11 @@ fn esc_to_go_up() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1586,8 +1670,11 @@ fn noop_when_pressing_esc_at_base_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events
@@ -1660,8 +1747,11 @@ fn delete_file() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -1670,7 +1760,7 @@ fn delete_file() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -1700,7 +1790,7 @@ fn delete_file() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -1708,6 +1798,73 @@ fn delete_file() {
     assert_snapshot!(&terminal_draw_events_mirror[4]);
     assert_snapshot!(&terminal_draw_events_mirror[5]);
     assert_snapshot!(&terminal_draw_events_mirror[6]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
+}
+
+#[test]
+fn delete_file_narrow_window() {
+    // the deletion progress has to fit in a message window half as wide
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(50, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("delete_file_narrow_window").expect("failed to create temp dir");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    let file_1_deleted = std::fs::metadata(&file_1_path).is_err();
+    let file_2_deleted = std::fs::metadata(&file_2_path).is_err();
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert!(file_1_deleted != file_2_deleted, "one file was deleted");
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
 }
 
 #[test]
@@ -1751,8 +1908,11 @@ fn delete_file_no_confirmation() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_DISABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -1761,7 +1921,7 @@ fn delete_file_no_confirmation() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -1791,7 +1951,7 @@ fn delete_file_no_confirmation() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 7);
+    assert_eq!(terminal_draw_events_mirror.len(), 8);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -1799,6 +1959,7 @@ fn delete_file_no_confirmation() {
     assert_snapshot!(&terminal_draw_events_mirror[4]);
     assert_snapshot!(&terminal_draw_events_mirror[5]);
     assert_snapshot!(&terminal_draw_events_mirror[6]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
 }
 
 #[test]
@@ -1844,8 +2005,11 @@ fn cant_delete_file_with_term_too_small() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -1932,8 +2096,11 @@ fn delete_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -1942,7 +2109,8 @@ fn delete_folder() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -1972,7 +2140,7 @@ fn delete_folder() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_eq!(terminal_draw_events_mirror.len(), 10);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -1982,6 +2150,7 @@ fn delete_folder() {
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
     assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
 }
 
 #[test]
@@ -2029,8 +2198,11 @@ fn delete_folder_no_confirmation() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_DISABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2039,7 +2211,7 @@ fn delete_folder_no_confirmation() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -2069,7 +2241,7 @@ fn delete_folder_no_confirmation() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2078,6 +2250,7 @@ fn delete_folder_no_confirmation() {
     assert_snapshot!(&terminal_draw_events_mirror[5]);
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
 }
 
 #[test]
@@ -2130,8 +2303,11 @@ fn delete_folder_small_window() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2141,7 +2317,7 @@ fn delete_folder_small_window() {
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
         HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
-        Flush, Clear, ShowCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -2171,7 +2347,7 @@ fn delete_folder_small_window() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 10);
+    assert_eq!(terminal_draw_events_mirror.len(), 11);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2182,6 +2358,7 @@ fn delete_folder_small_window() {
     assert_snapshot!(&terminal_draw_events_mirror[7]);
     assert_snapshot!(&terminal_draw_events_mirror[8]);
     assert_snapshot!(&terminal_draw_events_mirror[9]);
+    assert_snapshot!(&terminal_draw_events_mirror[10]);
 }
 
 #[test]
@@ -2232,8 +2409,11 @@ fn delete_folder_small_window_no_confirmation() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_DISABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2242,7 +2422,8 @@ fn delete_folder_small_window_no_confirmation() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -2272,7 +2453,7 @@ fn delete_folder_small_window_no_confirmation() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_eq!(terminal_draw_events_mirror.len(), 10);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2282,6 +2463,7 @@ fn delete_folder_small_window_no_confirmation() {
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
     assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
 }
 
 #[test]
@@ -2348,8 +2530,11 @@ fn delete_folder_with_multiple_children() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2358,7 +2543,8 @@ fn delete_folder_with_multiple_children() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
     ];
 
     assert_eq!(
@@ -2404,7 +2590,7 @@ fn delete_folder_with_multiple_children() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_eq!(terminal_draw_events_mirror.len(), 10);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2414,6 +2600,7 @@ fn delete_folder_with_multiple_children() {
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
     assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
 }
 
 #[test]
@@ -2479,8 +2666,11 @@ fn delete_folder_with_multiple_children_no_confirmation() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_DISABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2489,7 +2679,7 @@ fn delete_folder_with_multiple_children_no_confirmation() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
     assert_eq!(
         &terminal_events
@@ -2534,7 +2724,7 @@ fn delete_folder_with_multiple_children_no_confirmation() {
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
 
-    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2543,6 +2733,82 @@ fn delete_folder_with_multiple_children_no_confirmation() {
     assert_snapshot!(&terminal_draw_events_mirror[5]);
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
+}
+
+#[test]
+fn keep_deletion_progress_after_resize() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    // sent right away, so that it arrives while the folder is being deleted
+    events.push(Some(Event::Resize(190, 50)));
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("keep_deletion_progress_after_resize")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 8192).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    // the folder can't be moved to the trash, so it is deleted in the background
+    // (where deleting what's in it fails)
+    std::fs::set_permissions(&subfolder_1_path, Permissions::from_mode(0o555))
+        .expect("failed to set permissions");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::set_permissions(&subfolder_1_path, Permissions::from_mode(0o755))
+        .expect("failed to set permissions");
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
 }
 
 #[test]
@@ -2581,8 +2847,11 @@ fn pressing_delete_with_no_selected_tile() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2664,8 +2933,11 @@ fn delete_file_press_n() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2734,8 +3006,11 @@ fn files_with_size_zero() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -2762,8 +3037,11 @@ fn empty_folder() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -2822,8 +3100,11 @@ fn permission_denied_when_deleting() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2847,7 +3128,8 @@ fn permission_denied_when_deleting() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
     ];
 
     assert_eq!(
@@ -2857,7 +3139,7 @@ fn permission_denied_when_deleting() {
         &expected_terminal_events[..]
     );
 
-    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_eq!(terminal_draw_events_mirror.len(), 10);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2867,6 +3149,7 @@ fn permission_denied_when_deleting() {
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
     assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
 }
 #[cfg(not(target_os = "windows"))]
 #[test]
@@ -2909,8 +3192,11 @@ fn permission_denied_when_deleting_no_confirmation() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_DISABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            ..Default::default()
+        },
     );
     let terminal_draw_events_mirror = terminal_draw_events
         .lock()
@@ -2934,7 +3220,7 @@ fn permission_denied_when_deleting_no_confirmation() {
     let expected_terminal_events = vec![
         Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
         Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
-        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
     ];
 
     assert_eq!(
@@ -2944,7 +3230,7 @@ fn permission_denied_when_deleting_no_confirmation() {
         &expected_terminal_events[..]
     );
 
-    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
     assert_snapshot!(&terminal_draw_events_mirror[2]);
@@ -2953,6 +3239,7 @@ fn permission_denied_when_deleting_no_confirmation() {
     assert_snapshot!(&terminal_draw_events_mirror[5]);
     assert_snapshot!(&terminal_draw_events_mirror[6]);
     assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
 }
 
 #[test]
@@ -2976,8 +3263,11 @@ fn small_files_with_y_as_zero() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -3019,8 +3309,11 @@ fn small_files_with_x_as_zero() {
         backend,
         keyboard_events,
         temp_dir_path.clone(),
-        SHOW_APPARENT_SIZE,
-        DELETE_CONFIRMATION_ENABLED,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
     );
     std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
     let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
@@ -3036,3 +3329,1808 @@ fn small_files_with_x_as_zero() {
     assert_snapshot!(&terminal_draw_events_mirror[0]);
     assert_snapshot!(&terminal_draw_events_mirror[1]);
 }
+
+#[test]
+fn save_and_load_snapshot() {
+    let (_, scanned_draw_events, backend) = test_backend_factory(190, 50);
+    let keyboard_events = sleep_and_quit_events(1, true);
+    let temp_dir_path =
+        create_root_temp_dir("save_and_load_snapshot").expect("failed to create temp dir");
+    let mut snapshot_path = PathBuf::from(&temp_dir_path);
+    snapshot_path.set_extension("snapshot");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            save_snapshot: Some(snapshot_path.clone()),
+            ..Default::default()
+        },
+    );
+    // the snapshot should be shown as is, even though the folder is gone
+    std::fs::remove_dir_all(&temp_dir_path).expect("failed to remove temporary folder");
+
+    let (terminal_events, loaded_draw_events, backend) = test_backend_factory(190, 50);
+    let keyboard_events = sleep_and_quit_events(1, true);
+    let file_tree = FileTree::load_snapshot(&snapshot_path).expect("failed to load snapshot");
+    std::fs::remove_file(&snapshot_path).expect("failed to remove snapshot");
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            file_tree: Some(file_tree),
+            ..Default::default()
+        },
+    );
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events.lock().unwrap()[..],
+        &expected_terminal_events[..]
+    );
+
+    let scanned_draw_events_mirror = scanned_draw_events.lock().unwrap();
+    let loaded_draw_events_mirror = loaded_draw_events.lock().unwrap();
+    assert_eq!(loaded_draw_events_mirror.len(), 2);
+    assert_eq!(
+        &scanned_draw_events_mirror[..],
+        &loaded_draw_events_mirror[..]
+    );
+    assert_snapshot!(&loaded_draw_events_mirror[0]);
+}
+
+#[test]
+fn diff_two_scans() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+    let temp_dir_path = create_root_temp_dir("diff_two_scans").expect("failed to create temp dir");
+
+    let mut old_path = PathBuf::from(&temp_dir_path);
+    old_path.push("old");
+    let mut new_path = PathBuf::from(&temp_dir_path);
+    new_path.push("new");
+    for (path, grown_size, shrunk_size) in &[(&old_path, 4096, 16384), (&new_path, 12288, 8192)] {
+        let mut subfolder_path = PathBuf::from(path);
+        subfolder_path.push("subfolder1");
+        create_dir_all(&subfolder_path).expect("failed to create temporary directory");
+
+        let mut grown_file_path = PathBuf::from(&subfolder_path);
+        grown_file_path.push("grown_file");
+        create_temp_file(&grown_file_path, *grown_size).expect("failed to create temp file");
+
+        let mut shrunk_file_path = PathBuf::from(path);
+        shrunk_file_path.push("shrunk_file");
+        create_temp_file(&shrunk_file_path, *shrunk_size).expect("failed to create temp file");
+
+        let mut unchanged_file_path = PathBuf::from(path);
+        unchanged_file_path.push("unchanged_file");
+        create_temp_file(&unchanged_file_path, 4096).expect("failed to create temp file");
+    }
+
+    let old_file_tree = scan_folder(old_path, SHOW_APPARENT_SIZE, false, &ScanFilter::default());
+    let new_file_tree = scan_folder(
+        new_path.clone(),
+        SHOW_APPARENT_SIZE,
+        false,
+        &ScanFilter::default(),
+    );
+    start(
+        backend,
+        keyboard_events,
+        new_path,
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            file_tree: Some(diff_file_trees(&old_file_tree, &new_file_tree)),
+            ..Default::default()
+        },
+    );
+    std::fs::remove_dir_all(&temp_dir_path).expect("failed to remove temporary folder");
+    let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events.lock().unwrap()[..],
+        &expected_terminal_events[..]
+    );
+
+    assert_eq!(terminal_draw_events_mirror.len(), 4);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+}
+
+#[test]
+fn excluded_files_are_counted_in_title() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+    let keyboard_events = sleep_and_quit_events(1, true);
+    let temp_dir_path = create_root_temp_dir("excluded_files_are_counted_in_title")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("node_modules");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("node_modules");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3.log");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    let mut file_4_path = PathBuf::from(&temp_dir_path);
+    file_4_path.push("file4");
+    create_temp_file(&file_4_path, 4096).expect("failed to create temp file");
+
+    let scan_filter = ScanFilter::new(
+        &[String::from("node_modules"), String::from("*.log")],
+        false,
+    )
+    .expect("failed to create scan filter");
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            scan_filter,
+            ..Default::default()
+        },
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+    let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events.lock().unwrap()[..],
+        &expected_terminal_events[..]
+    );
+
+    assert_eq!(terminal_draw_events_mirror.len(), 2);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+}
+
+#[test]
+fn hard_links_are_counted_once() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+    let keyboard_events = sleep_and_quit_events(1, true);
+    let temp_dir_path =
+        create_root_temp_dir("hard_links_are_counted_once").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 8192).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("subfolder1");
+    file_2_path.push("file2");
+    std::fs::hard_link(&file_1_path, &file_2_path).expect("failed to create hard link");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+    let terminal_draw_events_mirror = terminal_draw_events.lock().unwrap();
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events.lock().unwrap()[..],
+        &expected_terminal_events[..]
+    );
+
+    assert_eq!(terminal_draw_events_mirror.len(), 2);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+}
+
+#[test]
+fn show_help() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char '?')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("show_help").expect("failed to create temp dir");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+
+    assert_eq!(terminal_draw_events_mirror.len(), 4);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+}
+
+#[test]
+fn rescan_current_folder() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Enter)));
+    events.push(None);
+    events.push(Some(key!(char 'r')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+
+    let temp_dir_path =
+        create_root_temp_dir("rescan_current_folder").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    let mut file_4_path = PathBuf::from(&temp_dir_path);
+    file_4_path.push("subfolder1");
+    file_4_path.push("file4");
+
+    // change the folder from "outside" right before we ask to rescan it
+    let keyboard_events = Box::new(TerminalEvents::new(events).inspect(move |event| {
+        if *event == key!(char 'r') {
+            std::fs::remove_file(&file_1_path).expect("failed to remove temp file");
+            create_temp_file(&file_4_path, 8192).expect("failed to create temp file");
+        }
+    }));
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+
+    assert_eq!(terminal_draw_events_mirror.len(), 7);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+}
+
+#[test]
+fn delete_file_choose_trash_or_delete() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 't')));
+    events.push(None);
+    events.push(Some(key!(char 'n')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("delete_file_choose_trash_or_delete")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            move_to_trash: true,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear,
+        ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert_eq!(
+        std::fs::metadata(&file_2_path).is_ok(),
+        true,
+        "file not deleted"
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 6);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+}
+
+#[test]
+fn undo_deletion() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(char 'u')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("undo_deletion").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            move_to_trash: true,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(std::fs::metadata(&file_2_path).is_ok(), "file was restored");
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_snapshot!(&terminal_draw_events_mirror[6]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+}
+
+#[test]
+fn undo_permanent_deletion() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(char 'u')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("undo_permanent_deletion").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(std::fs::metadata(&file_2_path).is_ok(), "file was restored");
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 9);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+}
+
+#[test]
+fn delete_marked_files() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(char ' ')));
+    events.push(None);
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(char ' ')));
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("delete_marked_files").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 12288).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(
+        std::fs::metadata(&file_2_path).is_err(),
+        "marked file was deleted"
+    );
+    assert!(
+        std::fs::metadata(&subfolder_1_path).is_err(),
+        "marked folder was deleted"
+    );
+    assert!(
+        std::fs::metadata(&file_3_path).is_ok(),
+        "unmarked file was not deleted"
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 12);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[11]);
+}
+
+#[test]
+fn delete_file_in_dry_run() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("delete_file_in_dry_run").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file 2's");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let script_path = PathBuf::from("/tmp/diskonaut_tests/delete_file_in_dry_run.sh");
+    let _ = std::fs::remove_file(&script_path);
+    let dry_run_script = DryRunScript::open(&script_path).expect("failed to open script");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            dry_run_script: Some(dry_run_script),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(
+        std::fs::metadata(&file_2_path).is_ok(),
+        "file was not deleted"
+    );
+    let script = std::fs::read_to_string(&script_path).expect("failed to read script");
+    assert_eq!(
+        script,
+        format!(
+            "#!/bin/sh\nrm -rf -- '{}/file 2'\\''s'\n",
+            temp_dir_path.to_string_lossy()
+        )
+    );
+    std::fs::remove_file(&script_path).expect("failed to remove script");
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+}
+
+#[test]
+fn cannot_delete_protected_file() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("cannot_delete_protected_file").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2.keep");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            protected_paths: ProtectedPaths::new(&[String::from("*.keep")])
+                .expect("failed to create protected paths"),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(
+        std::fs::metadata(&file_2_path).is_ok(),
+        "protected file was not deleted"
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 5);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+}
+
+#[test]
+fn cannot_delete_folder_containing_protected_folder() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("cannot_delete_folder_containing_protected_folder")
+        .expect("failed to create temp dir");
+
+    let mut git_folder_path = PathBuf::from(&temp_dir_path);
+    git_folder_path.push("project");
+    git_folder_path.push(".git");
+    create_dir_all(&git_folder_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&git_folder_path);
+    file_1_path.push("config");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            protected_paths: ProtectedPaths::new(&[String::from(".git")])
+                .expect("failed to create protected paths"),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(
+        std::fs::metadata(&file_1_path).is_ok(),
+        "protected folder was not deleted"
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 5);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+}
+
+#[test]
+fn cannot_delete_in_read_only_mode() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(char ' ')));
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("cannot_delete_in_read_only_mode").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_DISABLED,
+            read_only: true,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    assert!(
+        std::fs::metadata(&file_2_path).is_ok(),
+        "file was not deleted"
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 3);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+}
+
+#[test]
+fn record_deletion_in_audit_log() {
+    let (_terminal_events, _terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("record_deletion_in_audit_log").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let log_path = PathBuf::from("/tmp/diskonaut_tests/record_deletion_in_audit_log.log");
+    let _ = std::fs::remove_file(&log_path);
+    let audit_log = AuditLog::open(&log_path).expect("failed to open audit log");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            audit_log: Some(audit_log),
+            ..Default::default()
+        },
+    );
+
+    let log = std::fs::read_to_string(&log_path).expect("failed to read audit log");
+    std::fs::remove_file(&log_path).expect("failed to remove audit log");
+    std::fs::remove_dir_all(&temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(log.lines().count(), 1);
+    assert!(log.contains(&format!(
+        "\"path\":\"{}/file2\",\"type\":\"file\",\"size\":8192,\"descendants\":null,\"outcome\":\"deleted\",\"error\":null}}",
+        temp_dir_path.to_string_lossy()
+    )));
+}
+
+#[test]
+fn record_failed_deletion_in_audit_log() {
+    let (_terminal_events, _terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Backspace)));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("record_failed_deletion_in_audit_log")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 8192).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    // the folder itself can be moved, but nothing in it can be removed
+    std::fs::set_permissions(&subfolder_1_path, Permissions::from_mode(0o555))
+        .expect("failed to set permissions");
+
+    let log_path = PathBuf::from("/tmp/diskonaut_tests/record_failed_deletion_in_audit_log.log");
+    let _ = std::fs::remove_file(&log_path);
+    let audit_log = AuditLog::open(&log_path).expect("failed to open audit log");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            disable_delete_confirmation: DELETE_CONFIRMATION_ENABLED,
+            audit_log: Some(audit_log),
+            ..Default::default()
+        },
+    );
+
+    let file_left = std::fs::metadata(&file_1_path).is_ok();
+    let log = std::fs::read_to_string(&log_path).expect("failed to read audit log");
+    std::fs::remove_file(&log_path).expect("failed to remove audit log");
+    std::fs::set_permissions(&subfolder_1_path, Permissions::from_mode(0o755))
+        .expect("failed to set permissions");
+    std::fs::remove_dir_all(&temp_dir_path).expect("failed to remove temporary folder");
+
+    assert!(file_left, "folder was not deleted");
+    assert_eq!(log.lines().count(), 1);
+    assert!(log.contains(&format!(
+        "\"path\":\"{}/subfolder1\",\"type\":\"folder\",\"size\":8192,\"descendants\":1,\"outcome\":\"failed\"",
+        temp_dir_path.to_string_lossy()
+    )));
+}
+
+#[test]
+fn filter_current_folder_by_search() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char '/')));
+    events.push(None);
+    events.push(Some(key!(char 'f')));
+    events.push(None);
+    events.push(Some(key!(char 'I')));
+    events.push(None);
+    events.push(Some(key!(char '\n')));
+    events.push(None);
+    events.push(Some(key!(char 'l'))); // once to place selected marker on screen
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("filter_current_folder_by_search").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("subfolder1");
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 8);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+    assert_snapshot!(&terminal_draw_events_mirror[6]);
+}
+
+#[test]
+fn search_everywhere_and_jump_to_match() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char '/')));
+    events.push(None);
+    events.push(Some(key!(Tab)));
+    events.push(None);
+    events.push(Some(key!(char 'n')));
+    events.push(None);
+    events.push(Some(key!(char 'e')));
+    events.push(None);
+    events.push(Some(key!(Down)));
+    events.push(None);
+    events.push(Some(key!(char '\n')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("search_everywhere_and_jump_to_match")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_2_path = PathBuf::from(&temp_dir_path);
+    subfolder_2_path.push("subfolder1");
+    subfolder_2_path.push("subfolder2");
+    create_dir_all(&subfolder_2_path).expect("failed to create temporary directory");
+
+    let mut needle_1_path = PathBuf::from(&subfolder_2_path);
+    needle_1_path.push("needle1");
+    create_temp_file(&needle_1_path, 16384).expect("failed to create temp file");
+
+    let mut needle_2_path = PathBuf::from(&subfolder_2_path);
+    needle_2_path.push("needle2");
+    create_temp_file(&needle_2_path, 8192).expect("failed to create temp file");
+
+    let mut haystack_path = PathBuf::from(&subfolder_2_path);
+    haystack_path.push("haystack");
+    create_temp_file(&haystack_path, 65536).expect("failed to create temp file");
+
+    let mut file_1_path = PathBuf::from(&temp_dir_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    // every keystroke draws once as it's typed and once more when the search is done
+    assert_eq!(terminal_draw_events_mirror.len(), 11);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
+}
+
+#[test]
+fn search_everywhere_narrow_window() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(50, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char '/')));
+    events.push(None);
+    events.push(Some(key!(Tab)));
+    events.push(None);
+    events.push(Some(key!(char 'f')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("search_everywhere_narrow_window").expect("failed to create temp dir");
+
+    // enough matches that the title no longer fits
+    let mut base_folder = Folder::new(&temp_dir_path);
+    for index in 0..120 {
+        base_folder.add_file(PathBuf::from(format!("folder/file{}", index)), 4096);
+    }
+    let file_tree = FileTree::new(base_folder, temp_dir_path.clone(), SHOW_APPARENT_SIZE);
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            file_tree: Some(file_tree),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 7);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+}
+
+#[test]
+fn go_to_path_with_tab_completion() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'g')));
+    events.push(None);
+    events.push(Some(key!(char 's')));
+    events.push(None);
+    events.push(Some(key!(Tab)));
+    events.push(None);
+    events.push(Some(key!(char 's')));
+    events.push(None);
+    events.push(Some(key!(Tab)));
+    events.push(None);
+    events.push(Some(key!(char 'b')));
+    events.push(None);
+    events.push(Some(key!(Tab)));
+    events.push(None);
+    events.push(Some(key!(char '\n')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("go_to_path_with_tab_completion").expect("failed to create temp dir");
+
+    let mut subfolder_2_path = PathBuf::from(&temp_dir_path);
+    subfolder_2_path.push("subfolder1");
+    subfolder_2_path.push("subfolder2");
+    create_dir_all(&subfolder_2_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&subfolder_2_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("subfolder1");
+    file_2_path.push("summary");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 11);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+    assert_snapshot!(&terminal_draw_events_mirror[7]);
+    assert_snapshot!(&terminal_draw_events_mirror[8]);
+    assert_snapshot!(&terminal_draw_events_mirror[9]);
+}
+
+#[test]
+fn open_sub_path_on_start() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("open_sub_path_on_start").expect("failed to create temp dir");
+
+    let mut subfolder_2_path = PathBuf::from(&temp_dir_path);
+    subfolder_2_path.push("subfolder1");
+    subfolder_2_path.push("subfolder2");
+    create_dir_all(&subfolder_2_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&subfolder_2_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("subfolder1");
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 8192).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("file3");
+    create_temp_file(&file_3_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            open_path: Some(PathBuf::from("subfolder1/subfolder2")),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 4);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+}
+
+#[test]
+fn open_missing_sub_path_on_start() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("open_missing_sub_path_on_start").expect("failed to create temp dir");
+
+    let mut subfolder_1_path = PathBuf::from(&temp_dir_path);
+    subfolder_1_path.push("subfolder1");
+    create_dir_all(&subfolder_1_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&subfolder_1_path);
+    file_1_path.push("file1");
+    create_temp_file(&file_1_path, 16384).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            open_path: Some(PathBuf::from("subfolder1/file1")),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 3);
+    assert_snapshot!(&terminal_draw_events_mirror[0]);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+}
+
+#[test]
+fn group_by_file_type() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 't')));
+    events.push(None);
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(char '\n')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(char 't')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path =
+        create_root_temp_dir("group_by_file_type").expect("failed to create temp dir");
+
+    let mut subfolder_2_path = PathBuf::from(&temp_dir_path);
+    subfolder_2_path.push("subfolder1");
+    subfolder_2_path.push("subfolder2");
+    create_dir_all(&subfolder_2_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&subfolder_2_path);
+    file_1_path.push("movie.mkv");
+    create_temp_file(&file_1_path, 32768).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&temp_dir_path);
+    file_2_path.push("subfolder1");
+    file_2_path.push("clip.mp4");
+    create_temp_file(&file_2_path, 16384).expect("failed to create temp file");
+
+    let mut file_3_path = PathBuf::from(&temp_dir_path);
+    file_3_path.push("subfolder1");
+    file_3_path.push("build.log");
+    create_temp_file(&file_3_path, 8192).expect("failed to create temp file");
+
+    let mut file_4_path = PathBuf::from(&temp_dir_path);
+    file_4_path.push("notes.txt");
+    create_temp_file(&file_4_path, 4096).expect("failed to create temp file");
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw,
+        HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 7);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+    assert_snapshot!(&terminal_draw_events_mirror[5]);
+}
+
+#[test]
+fn show_largest_files_and_jump_to_one() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'f')));
+    events.push(None);
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(char '\n')));
+    events.push(None);
+    events.push(Some(key!(Esc)));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("show_largest_files_and_jump_to_one")
+        .expect("failed to create temp dir");
+
+    let mut subfolder_2_path = PathBuf::from(&temp_dir_path);
+    subfolder_2_path.push("subfolder1");
+    subfolder_2_path.push("subfolder2");
+    create_dir_all(&subfolder_2_path).expect("failed to create temporary directory");
+
+    let mut file_1_path = PathBuf::from(&subfolder_2_path);
+    file_1_path.push("core");
+    create_temp_file(&file_1_path, 32768).expect("failed to create temp file");
+
+    let mut file_2_path = PathBuf::from(&subfolder_2_path);
+    file_2_path.push("file2");
+    create_temp_file(&file_2_path, 4096).expect("failed to create temp file");
+
+    for i in 3..6 {
+        let mut file_path = PathBuf::from(&temp_dir_path);
+        file_path.push(format!("file{}", i));
+        create_temp_file(&file_path, 8192).expect("failed to create temp file");
+    }
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear,
+        ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 6);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+    assert_snapshot!(&terminal_draw_events_mirror[4]);
+}
+
+#[test]
+fn toggle_between_apparent_and_disk_size() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+    let backend = backend.with_disk_sizes();
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(char 'a')));
+    events.push(None);
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("toggle_between_apparent_and_disk_size")
+        .expect("failed to create temp dir");
+
+    // the sizes on disk depend on the filesystem, so they are made up
+    let mut base_folder = Folder::new(&temp_dir_path);
+    base_folder.add_sized_file(PathBuf::from("sparse_image"), 1048576, 4096);
+    base_folder.add_sized_file(PathBuf::from("compressed_log"), 65536, 16384);
+    base_folder.add_sized_file(PathBuf::from("subfolder1/file1"), 8192, 8192);
+    base_folder.add_sized_file(PathBuf::from("subfolder1/small_file"), 100, 4096);
+    let mut file_tree = FileTree::new(base_folder, temp_dir_path.clone(), SHOW_APPARENT_SIZE);
+    file_tree.set_show_apparent_size(SHOW_APPARENT_SIZE);
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            file_tree: Some(file_tree),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 5);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+    assert_snapshot!(&terminal_draw_events_mirror[2]);
+    assert_snapshot!(&terminal_draw_events_mirror[3]);
+}
+
+#[test]
+fn show_both_sizes_when_they_are_equal() {
+    let (terminal_events, terminal_draw_events, backend) = test_backend_factory(190, 50);
+    let backend = backend.with_disk_sizes();
+    let mut events: Vec<Option<Event>> = iter::repeat(None).take(1).collect();
+    events.push(Some(key!(char 'l')));
+    events.push(None);
+    events.push(Some(key!(ctrl 'c')));
+    events.push(None);
+    events.push(Some(key!(char 'y')));
+    let keyboard_events = Box::new(TerminalEvents::new(events));
+
+    let temp_dir_path = create_root_temp_dir("show_both_sizes_when_they_are_equal")
+        .expect("failed to create temp dir");
+
+    let mut base_folder = Folder::new(&temp_dir_path);
+    base_folder.add_sized_file(PathBuf::from("file1"), 8192, 8192);
+    base_folder.add_sized_file(PathBuf::from("subfolder1/file2"), 4096, 4096);
+    let file_tree = FileTree::new(base_folder, temp_dir_path.clone(), SHOW_APPARENT_SIZE);
+
+    start(
+        backend,
+        keyboard_events,
+        temp_dir_path.clone(),
+        StartOptions {
+            show_apparent_size: SHOW_APPARENT_SIZE,
+            file_tree: Some(file_tree),
+            ..Default::default()
+        },
+    );
+    let terminal_draw_events_mirror = terminal_draw_events
+        .lock()
+        .expect("could not acquire lock on terminal events");
+
+    let expected_terminal_events = vec![
+        Clear, HideCursor, Draw, HideCursor, Flush, Draw, HideCursor, Flush, Draw, HideCursor,
+        Flush, Clear, ShowCursor,
+    ];
+    assert_eq!(
+        &terminal_events
+            .lock()
+            .expect("could not acquire lock on terminal_events")[..],
+        &expected_terminal_events[..]
+    );
+    std::fs::remove_dir_all(temp_dir_path).expect("failed to remove temporary folder");
+
+    assert_eq!(terminal_draw_events_mirror.len(), 3);
+    assert_snapshot!(&terminal_draw_events_mirror[1]);
+}
@@ -2,9 +2,11 @@ use ::std::collections::HashMap;
 use ::std::io;
 use ::std::sync::{Arc, Mutex};
 use ::tui::backend::Backend;
-use ::tui::buffer::Cell;
+use ::tui::buffer::{Buffer, Cell};
 use ::tui::layout::Rect;
 
+use ::regex::Regex;
+
 #[derive(Hash, Debug, PartialEq)]
 pub enum TerminalEvent {
     Clear,
@@ -20,6 +22,61 @@ pub struct TestBackend {
     pub draw_events: Arc<Mutex<Vec<String>>>,
     terminal_width: Arc<Mutex<u16>>,
     terminal_height: Arc<Mutex<u16>>,
+    // how much a file takes on disk depends on the filesystem the tests run on
+    // (eg. its block size, or whether it uses compression), so unless the test
+    // made up these sizes we hide them. which cells are redrawn depends on them
+    // too, so we keep the whole screen and draw what changed once they are hidden
+    redact_disk_sizes: bool,
+    screen: Buffer,
+    redacted_screen: Buffer,
+}
+
+fn redact_row(row: &mut [Cell], redactions: &[Regex]) {
+    // replaces the first group of every match in the row with a '?' that has its style
+    let mut text = String::new();
+    let mut cell_at_offset = vec![];
+    for (index, cell) in row.iter().enumerate() {
+        text.push_str(&cell.symbol);
+        cell_at_offset.extend(cell.symbol.bytes().map(|_| index));
+    }
+    cell_at_offset.push(row.len());
+    let mut ranges: Vec<(usize, usize)> = redactions
+        .iter()
+        .flat_map(|redaction| redaction.captures_iter(&text))
+        .filter_map(|captures| captures.get(1))
+        .map(|redacted| {
+            (
+                cell_at_offset[redacted.start()],
+                cell_at_offset[redacted.end()],
+            )
+        })
+        .collect();
+    // from the right, so that the cells of the ranges left to redact stay in place
+    ranges.sort();
+    for (start, end) in ranges.into_iter().rev() {
+        let mut redacted = row[start].clone();
+        redacted.set_symbol("?");
+        let mut redacted_row = row[..start].to_vec();
+        redacted_row.push(redacted);
+        redacted_row.extend_from_slice(&row[end..]);
+        redacted_row.resize(row.len(), Cell::default());
+        row.clone_from_slice(&redacted_row);
+    }
+}
+
+fn redact_disk_sizes(screen: &Buffer) -> Buffer {
+    let redactions = [
+        Regex::new(r"([0-9.]+[KMG]?) on disk").expect("invalid regex"),
+        Regex::new(r"(?:apparent|on disk), (<0\.01|[0-9]+\.[0-9]{2})x").expect("invalid regex"),
+    ];
+    let mut redacted_screen = screen.clone();
+    let width = screen.area.width as usize;
+    if width > 0 {
+        for row in redacted_screen.content.chunks_mut(width) {
+            redact_row(row, &redactions);
+        }
+    }
+    redacted_screen
 }
 
 impl TestBackend {
@@ -34,7 +91,37 @@ impl TestBackend {
             draw_events: draw_log,
             terminal_width,
             terminal_height,
+            redact_disk_sizes: true,
+            screen: Buffer::default(),
+            redacted_screen: Buffer::default(),
+        }
+    }
+    pub fn with_disk_sizes(mut self) -> Self {
+        // for tests that made up the sizes on disk, rather than scanning files
+        self.redact_disk_sizes = false;
+        self
+    }
+    fn redact<'a, I>(&mut self, content: I) -> Vec<(u16, u16, Cell)>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let area = self.size().expect("could not get terminal size");
+        if self.screen.area != area {
+            self.screen = Buffer::empty(area);
+            self.redacted_screen = Buffer::empty(area);
         }
+        for (x, y, cell) in content {
+            *self.screen.get_mut(x, y) = cell.clone();
+        }
+        let redacted_screen = redact_disk_sizes(&self.screen);
+        let changes = self
+            .redacted_screen
+            .diff(&redacted_screen)
+            .into_iter()
+            .map(|(x, y, cell)| (x, y, cell.clone()))
+            .collect();
+        self.redacted_screen = redacted_screen;
+        changes
     }
 }
 
@@ -47,6 +134,9 @@ struct Point {
 impl Backend for TestBackend {
     fn clear(&mut self) -> io::Result<()> {
         self.events.lock().unwrap().push(TerminalEvent::Clear);
+        // everything is drawn again after this
+        self.screen.reset();
+        self.redacted_screen.reset();
         Ok(())
     }
 
@@ -75,9 +165,17 @@ impl Backend for TestBackend {
     {
         self.events.lock().unwrap().push(TerminalEvent::Draw);
         let mut string = String::with_capacity(content.size_hint().0 * 3);
+        let redacted_content;
         let mut coordinates = HashMap::new();
-        for (x, y, cell) in content {
-            coordinates.insert(Point { x, y }, cell);
+        if self.redact_disk_sizes {
+            redacted_content = self.redact(content);
+            for (x, y, cell) in &redacted_content {
+                coordinates.insert(Point { x: *x, y: *y }, cell);
+            }
+        } else {
+            for (x, y, cell) in content {
+                coordinates.insert(Point { x, y }, cell);
+            }
         }
         let terminal_height = self.terminal_height.lock().unwrap();
         let terminal_width = self.terminal_width.lock().unwrap();
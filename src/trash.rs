@@ -0,0 +1,332 @@
+// moving files to the trash as described in the freedesktop.org trash specification:
+// https://specifications.freedesktop.org/trash-spec/trashspec-latest.html
+use ::std::fs;
+use ::std::io;
+use ::std::path::{Path, PathBuf};
+
+// a file or folder we moved to the trash, which can be put back where it was
+#[derive(Debug, Clone)]
+pub struct TrashedFile {
+    pub original_path: PathBuf,
+    pub trashed_path: PathBuf,
+    info_path: PathBuf,
+}
+
+impl TrashedFile {
+    pub fn restore(&self) -> io::Result<()> {
+        if fs::symlink_metadata(&self.original_path).is_ok() {
+            // rename would happily replace an empty folder or a file
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "a file with the same name already exists",
+            ));
+        }
+        fs::rename(&self.trashed_path, &self.original_path)?;
+        let _ = fs::remove_file(&self.info_path);
+        Ok(())
+    }
+    // once the file is removed from the trash for good, so is what we wrote about it
+    pub fn remove_trash_info(&self) -> io::Result<()> {
+        fs::remove_file(&self.info_path)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn move_to_trash(path: &Path) -> io::Result<TrashedFile> {
+    freedesktop::move_to_trash(path, freedesktop::home_trash().as_deref())
+}
+
+#[cfg(target_os = "windows")]
+pub fn move_to_trash(_path: &Path) -> io::Result<TrashedFile> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "Moving to trash is not supported on this platform",
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+mod freedesktop {
+    #[cfg(not(test))]
+    use ::std::env;
+    use ::std::ffi::{OsStr, OsString};
+    use ::std::fs::{self, DirBuilder, OpenOptions};
+    use ::std::io::{self, Write};
+    use ::std::os::unix::ffi::OsStrExt;
+    use ::std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+    use ::std::path::{Path, PathBuf};
+    use ::std::time::{SystemTime, UNIX_EPOCH};
+
+    use ::nix::libc;
+    use ::nix::unistd::getuid;
+
+    use super::TrashedFile;
+
+    // the sticky bit, which the admin created .Trash folder must have
+    const S_ISVTX: u32 = 0o1000;
+
+    struct TrashFolder {
+        path: PathBuf,
+        // the top folder of the mount this trash is in, None for the home trash
+        // (paths in the home trash are absolute, in others they are relative to this)
+        topdir: Option<PathBuf>,
+    }
+
+    #[cfg(test)]
+    pub fn home_trash() -> Option<PathBuf> {
+        // so that tests do not depend on (or fill up) the trash of whoever runs them
+        Some(PathBuf::from("/tmp/diskonaut_tests/Trash"))
+    }
+
+    #[cfg(not(test))]
+    pub fn home_trash() -> Option<PathBuf> {
+        let data_home = match env::var_os("XDG_DATA_HOME") {
+            Some(data_home) if Path::new(&data_home).is_absolute() => PathBuf::from(data_home),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+        };
+        Some(data_home.join("Trash"))
+    }
+
+    fn device_id(path: &Path) -> Option<u64> {
+        // the trash folders might not exist yet, so we look at the closest folder that does
+        path.ancestors()
+            .find_map(|ancestor| fs::symlink_metadata(ancestor).ok())
+            .map(|metadata| metadata.dev())
+    }
+
+    fn topdir(path: &Path, device_id: u64) -> PathBuf {
+        // the folder this filesystem is mounted on
+        let mut topdir = path;
+        for ancestor in path.ancestors().skip(1) {
+            match fs::symlink_metadata(ancestor) {
+                Ok(metadata) if metadata.dev() == device_id => topdir = ancestor,
+                _ => break,
+            }
+        }
+        topdir.to_path_buf()
+    }
+
+    fn create_trash_folder(path: &Path) -> io::Result<()> {
+        let mut dir_builder = DirBuilder::new();
+        dir_builder.recursive(true).mode(0o700);
+        dir_builder.create(path.join("files"))?;
+        dir_builder.create(path.join("info"))
+    }
+
+    fn is_admin_trash(path: &Path) -> bool {
+        // a .Trash folder created by the admin, which should not be
+        // a symlink and must have the sticky bit set
+        match fs::symlink_metadata(path) {
+            Ok(metadata) => metadata.is_dir() && metadata.permissions().mode() & S_ISVTX != 0,
+            Err(_) => false,
+        }
+    }
+
+    fn find_trash_folder(
+        path: &Path,
+        device_id: u64,
+        home_trash: Option<&Path>,
+    ) -> io::Result<TrashFolder> {
+        if let Some(home_trash) = home_trash {
+            if self::device_id(home_trash) == Some(device_id) {
+                create_trash_folder(home_trash)?;
+                return Ok(TrashFolder {
+                    path: home_trash.to_path_buf(),
+                    topdir: None,
+                });
+            }
+        }
+        let topdir = topdir(path, device_id);
+        let uid = getuid();
+        let admin_trash = topdir.join(".Trash");
+        if is_admin_trash(&admin_trash) {
+            let user_trash = admin_trash.join(uid.to_string());
+            if create_trash_folder(&user_trash).is_ok() {
+                return Ok(TrashFolder {
+                    path: user_trash,
+                    topdir: Some(topdir),
+                });
+            }
+        }
+        let user_trash = topdir.join(format!(".Trash-{}", uid));
+        create_trash_folder(&user_trash)?;
+        Ok(TrashFolder {
+            path: user_trash,
+            topdir: Some(topdir),
+        })
+    }
+
+    fn url_escape(path: &Path) -> String {
+        let mut escaped = String::new();
+        for byte in path.as_os_str().as_bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                    escaped.push(*byte as char)
+                }
+                _ => escaped.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        escaped
+    }
+
+    fn deletion_date() -> String {
+        // in local time, without a timezone
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0) as libc::time_t;
+        let mut tm: libc::tm = unsafe { ::std::mem::zeroed() };
+        unsafe { libc::localtime_r(&now, &mut tm) };
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min,
+            tm.tm_sec
+        )
+    }
+
+    fn trashed_name(file_name: &OsStr, attempt: u32) -> OsString {
+        let mut name = file_name.to_os_string();
+        if attempt > 1 {
+            name.push(format!(".{}", attempt));
+        }
+        name
+    }
+
+    fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot trash this path"))?;
+        // we canonicalize the parent rather than the path, since the path might be a symlink
+        let parent = match path.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        };
+        Ok(fs::canonicalize(parent)?.join(file_name))
+    }
+
+    pub fn move_to_trash(path: &Path, home_trash: Option<&Path>) -> io::Result<TrashedFile> {
+        let path = absolute_path(path)?;
+        let file_name = path.file_name().expect("absolute path has no file name");
+        let device_id = fs::symlink_metadata(&path)?.dev();
+        let trash_folder = find_trash_folder(&path, device_id, home_trash)?;
+        let original_path = match &trash_folder.topdir {
+            Some(topdir) => path.strip_prefix(topdir).unwrap_or(&path),
+            None => &path,
+        };
+        let trash_info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            url_escape(original_path),
+            deletion_date()
+        );
+        let mut attempt = 1;
+        loop {
+            // creating the info file is what reserves the name in the trash
+            let name = trashed_name(file_name, attempt);
+            let mut info_file_name = name.clone();
+            info_file_name.push(".trashinfo");
+            let info_path = trash_folder.path.join("info").join(info_file_name);
+            let trashed_path = trash_folder.path.join("files").join(&name);
+            attempt += 1;
+            if fs::symlink_metadata(&trashed_path).is_ok() {
+                continue;
+            }
+            let mut info_file = match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(info_file) => info_file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            let moved = info_file
+                .write_all(trash_info.as_bytes())
+                .and_then(|_| fs::rename(&path, &trashed_path));
+            if let Err(e) = moved {
+                let _ = fs::remove_file(&info_path);
+                return Err(e);
+            }
+            return Ok(TrashedFile {
+                original_path: path,
+                trashed_path,
+                info_path,
+            });
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ::std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+
+        #[test]
+        fn move_files_and_folders_to_trash() {
+            let path = PathBuf::from("/tmp/diskonaut_tests/trash");
+            remove_dir_all(&path).ok();
+            let home_trash = path.join("Trash");
+            create_dir_all(path.join("folder with spaces")).expect("failed to create folder");
+            write(path.join("folder with spaces/file"), "x").expect("failed to create file");
+            write(path.join("file"), "x").expect("failed to create file");
+            let second_file = path.join("folder with spaces/file");
+
+            move_to_trash(&path.join("file"), Some(&home_trash)).expect("failed to trash file");
+            move_to_trash(&second_file, Some(&home_trash)).expect("failed to trash file");
+            move_to_trash(&path.join("folder with spaces"), Some(&home_trash))
+                .expect("failed to trash folder");
+
+            let first_info = read_to_string(home_trash.join("info/file.trashinfo"))
+                .expect("failed to read trash info");
+            let second_info = read_to_string(home_trash.join("info/file.2.trashinfo"))
+                .expect("failed to read trash info");
+            let folder_info = read_to_string(home_trash.join("info/folder with spaces.trashinfo"))
+                .expect("failed to read trash info");
+            let files_left = fs::read_dir(&path).expect("failed to read folder").count();
+            let trashed_files = fs::read_dir(home_trash.join("files"))
+                .expect("failed to read trash")
+                .count();
+            remove_dir_all(&path).ok();
+
+            assert!(first_info
+                .starts_with("[Trash Info]\nPath=/tmp/diskonaut_tests/trash/file\nDeletionDate="));
+            assert!(second_info.starts_with(
+                "[Trash Info]\nPath=/tmp/diskonaut_tests/trash/folder%20with%20spaces/file\n"
+            ));
+            assert!(folder_info.starts_with(
+                "[Trash Info]\nPath=/tmp/diskonaut_tests/trash/folder%20with%20spaces\n"
+            ));
+            // only the trash is left
+            assert_eq!(files_left, 1);
+            assert_eq!(trashed_files, 3);
+        }
+
+        #[test]
+        fn restore_trashed_files() {
+            let path = PathBuf::from("/tmp/diskonaut_tests/trash_restore");
+            remove_dir_all(&path).ok();
+            let home_trash = path.join("Trash");
+            create_dir_all(path.join("folder")).expect("failed to create folder");
+            write(path.join("folder/file"), "x").expect("failed to create file");
+            write(path.join("file"), "x").expect("failed to create file");
+
+            let trashed_file =
+                move_to_trash(&path.join("file"), Some(&home_trash)).expect("failed to trash file");
+            let trashed_folder = move_to_trash(&path.join("folder"), Some(&home_trash))
+                .expect("failed to trash folder");
+            let was_removed = !path.join("file").exists() && !path.join("folder").exists();
+            trashed_file.restore().expect("failed to restore file");
+            trashed_folder.restore().expect("failed to restore folder");
+            let was_restored = path.join("file").exists() && path.join("folder/file").exists();
+            let info_files = fs::read_dir(home_trash.join("info"))
+                .expect("failed to read trash")
+                .count();
+            remove_dir_all(&path).ok();
+
+            assert!(was_removed);
+            assert!(was_restored);
+            assert_eq!(info_files, 0);
+        }
+    }
+}
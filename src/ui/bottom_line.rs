@@ -4,14 +4,56 @@ use ::tui::layout::Rect;
 use ::tui::style::{Color, Modifier, Style};
 use ::tui::widgets::Widget;
 
-use crate::state::tiles::{FileType, Tile};
-use crate::ui::format::{truncate_middle, DisplaySize};
+use crate::state::path_prompt::PathPrompt;
+use crate::state::search::Search;
+use crate::state::tiles::{FileType, OwnerFilter, OwnerKind, Tile, View};
+use crate::ui::format::{truncate_end, truncate_middle, DisplayGrowth, DisplaySize};
 
-fn render_currently_selected(buf: &mut Buffer, currently_selected: &Tile, max_len: u16, y: u16) {
+fn both_sizes(currently_selected: &Tile, show_apparent_size: bool) -> Option<String> {
+    // the size we show first, then the other one and how they compare
+    let (apparent_size, disk_size) = (
+        currently_selected.apparent_size,
+        currently_selected.disk_size,
+    );
+    if currently_selected.growth.is_some() {
+        // a comparison between two scans only has the sizes it was made with
+        return None;
+    }
+    let apparent_size_legend = format!("{} apparent", DisplaySize(apparent_size as f64));
+    let disk_size_legend = format!("{} on disk", DisplaySize(disk_size as f64));
+    let sizes = if show_apparent_size {
+        format!("{}, {}", apparent_size_legend, disk_size_legend)
+    } else {
+        format!("{}, {}", disk_size_legend, apparent_size_legend)
+    };
+    if apparent_size == 0 {
+        Some(sizes)
+    } else {
+        // eg. sparse files take next to nothing on disk
+        let ratio = disk_size as f64 / apparent_size as f64;
+        if ratio < 0.01 {
+            Some(format!("{}, <0.01x", sizes))
+        } else {
+            Some(format!("{}, {:.2}x", sizes, ratio))
+        }
+    }
+}
+
+fn render_currently_selected(
+    buf: &mut Buffer,
+    currently_selected: &Tile,
+    show_apparent_size: bool,
+    max_len: u16,
+    y: u16,
+) {
     let file_name = currently_selected.name.to_string_lossy();
-    let size = DisplaySize(currently_selected.size as f64);
+    let size = match currently_selected.growth {
+        Some(growth) => format!("{}", DisplayGrowth(growth)),
+        None => format!("{}", DisplaySize(currently_selected.size as f64)),
+    };
+    let both_sizes = both_sizes(currently_selected, show_apparent_size);
     let descendants = currently_selected.descendants;
-    let (style, lines) = match currently_selected.file_type {
+    let (style, mut lines) = match currently_selected.file_type {
         FileType::File => (
             Style::default().add_modifier(Modifier::BOLD),
             vec![
@@ -37,6 +79,16 @@ fn render_currently_selected(buf: &mut Buffer, currently_selected: &Tile, max_le
             ],
         ),
     };
+    if let Some(both_sizes) = both_sizes {
+        let line = match descendants {
+            Some(descendants) => format!(
+                "SELECTED: {} ({}, {} files)",
+                file_name, both_sizes, descendants
+            ),
+            None => format!("SELECTED: {} ({})", file_name, both_sizes),
+        };
+        lines.insert(0, line);
+    }
     for line in lines {
         if (line.chars().count() as u16) < max_len {
             buf.set_string(1, y, line, style);
@@ -98,6 +150,80 @@ fn render_controls_legend(buf: &mut Buffer, hide_delete: bool, max_len: u16, y:
     }
 }
 
+fn render_search_prompt(buf: &mut Buffer, search: &Search, max_len: u16, y: u16) {
+    let kind = match (search.is_recursive, search.is_regex) {
+        (false, false) => "Search this folder",
+        (false, true) => "Search this folder (regex)",
+        (true, false) => "Search everywhere",
+        (true, true) => "Search everywhere (regex)",
+    };
+    let prompt = format!("{}: /{}", kind, search.query);
+    let legend = if search.is_recursive {
+        "<TAB> - this folder, <C-r> - regex, <ENTER> - go to match, <ESC> - cancel"
+    } else {
+        "<TAB> - everywhere, <C-r> - regex, <ENTER> - done, <ESC> - cancel"
+    };
+    // an invalid regex is shown in red until it's fixed
+    let prompt_style = if search.is_invalid() {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let prompt_len = prompt.chars().count() as u16;
+    let legend_len = legend.chars().count() as u16;
+    if max_len >= prompt_len + legend_len + 3 {
+        buf.set_string(1, y, &prompt, prompt_style);
+        buf.set_string(max_len - legend_len, y, legend, Style::default());
+    } else if max_len > prompt_len {
+        buf.set_string(1, y, &prompt, prompt_style);
+    } else {
+        // the end of the query is where we're typing
+        let query = format!("/{}", search.query);
+        let skip = query.chars().count().saturating_sub(max_len as usize);
+        let query: String = query.chars().skip(skip).collect();
+        buf.set_string(1, y, query, prompt_style);
+    }
+}
+
+fn render_path_prompt(buf: &mut Buffer, path_prompt: &PathPrompt, max_len: u16, y: u16) {
+    let prompt = format!("Go to: {}", path_prompt.input);
+    let legend = "<TAB> - complete, <ENTER> - go, <ESC> - cancel";
+    let prompt_style = if path_prompt.not_found {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let prompt_len = prompt.chars().count() as u16;
+    let legend_len = legend.chars().count() as u16;
+    if max_len <= prompt_len {
+        // the end of the path is where we're typing
+        let skip = prompt.chars().count() - max_len as usize + 1;
+        let prompt: String = prompt.chars().skip(skip).collect();
+        buf.set_string(1, y, prompt, prompt_style);
+        return;
+    }
+    buf.set_string(1, y, &prompt, prompt_style);
+    let show_legend = max_len >= prompt_len + legend_len + 4;
+    // the completions go between the prompt and the legend
+    let completions_len = if show_legend {
+        max_len - prompt_len - legend_len - 4
+    } else {
+        (max_len - prompt_len).saturating_sub(3)
+    };
+    if !path_prompt.completions.is_empty() {
+        let completions = truncate_end(&path_prompt.completions.join("  "), completions_len);
+        buf.set_string(
+            prompt_len + 3,
+            y,
+            completions,
+            Style::default().fg(Color::Blue),
+        );
+    }
+    if show_legend {
+        buf.set_string(max_len - legend_len, y, legend, Style::default());
+    }
+}
+
 fn render_small_files_legend(buf: &mut Buffer, x: u16, y: u16, small_files_legend: &str) {
     buf.set_string(
         x,
@@ -114,7 +240,15 @@ fn render_small_files_legend(buf: &mut Buffer, x: u16, y: u16, small_files_legen
 
 pub struct BottomLine<'a> {
     hide_delete: bool,
+    undo_available: bool,
+    marked: Option<(usize, u128)>,
+    search: Option<&'a Search>,
+    path_prompt: Option<&'a PathPrompt>,
+    search_filter: Option<&'a str>,
+    view: Option<&'a View>,
+    owner_filter: Option<&'a OwnerFilter>,
     hide_small_files_legend: bool,
+    show_apparent_size: bool,
     currently_selected: Option<&'a Tile>,
     last_read_path: Option<&'a PathBuf>,
 }
@@ -123,13 +257,53 @@ impl<'a> BottomLine<'a> {
     pub fn new() -> Self {
         Self {
             hide_delete: false,
+            undo_available: false,
+            marked: None,
+            search: None,
+            path_prompt: None,
+            search_filter: None,
+            view: None,
+            owner_filter: None,
             hide_small_files_legend: false,
+            show_apparent_size: false,
             currently_selected: None,
             last_read_path: None,
         }
     }
-    pub fn hide_delete(mut self) -> Self {
-        self.hide_delete = true;
+    pub fn hide_delete(mut self, should_hide_delete: bool) -> Self {
+        self.hide_delete = should_hide_delete;
+        self
+    }
+    pub fn undo_available(mut self, undo_available: bool) -> Self {
+        self.undo_available = undo_available;
+        self
+    }
+    pub fn marked(mut self, marked: Option<(usize, u128)>) -> Self {
+        self.marked = marked;
+        self
+    }
+    pub fn search(mut self, search: Option<&'a Search>) -> Self {
+        self.search = search;
+        self
+    }
+    pub fn path_prompt(mut self, path_prompt: Option<&'a PathPrompt>) -> Self {
+        self.path_prompt = path_prompt;
+        self
+    }
+    pub fn search_filter(mut self, search_filter: Option<&'a str>) -> Self {
+        self.search_filter = search_filter;
+        self
+    }
+    pub fn owner_filter(mut self, owner_filter: Option<&'a OwnerFilter>) -> Self {
+        self.owner_filter = owner_filter;
+        self
+    }
+    pub fn view(mut self, view: &'a View) -> Self {
+        self.view = Some(view);
+        self
+    }
+    pub fn show_apparent_size(mut self, show_apparent_size: bool) -> Self {
+        self.show_apparent_size = show_apparent_size;
         self
     }
     pub fn hide_small_files_legend(mut self, should_hide_small_files_legend: bool) -> Self {
@@ -154,12 +328,74 @@ impl<'a> Widget for BottomLine<'a> {
         } else {
             small_files_legend.chars().count() as u16
         };
-        let max_status_len = area.width - small_files_len - 1;
+        let undo_legend = "Undo available (u)";
+        let undo_len = undo_legend.chars().count() as u16 + 2;
+        // the undo legend only gets whatever room the status line does not need
+        let show_undo_legend =
+            self.undo_available && area.width > small_files_len + undo_len + 1 + 50;
+        let undo_len = if show_undo_legend { undo_len } else { 0 };
+        let marked_legend = self.marked.map(|(marked_count, marked_size)| {
+            format!(
+                "marked: {} items, {}",
+                marked_count,
+                DisplaySize(marked_size as f64)
+            )
+        });
+        let marked_len = match &marked_legend {
+            Some(marked_legend) => marked_legend.chars().count() as u16 + 2,
+            None => 0,
+        };
+        let show_marked_legend =
+            marked_len > 0 && area.width > small_files_len + undo_len + marked_len + 1 + 50;
+        let marked_len = if show_marked_legend { marked_len } else { 0 };
+        let view_legend = match self.view {
+            Some(View::Categories) => Some(String::from("by file type")),
+            Some(View::LargestFiles) => Some(String::from("largest files")),
+            Some(View::Owners(OwnerKind::User)) => Some(String::from("by user")),
+            Some(View::Owners(OwnerKind::Group)) => Some(String::from("by group")),
+            Some(View::Category(category)) => {
+                Some(format!("file type: {}", category.to_string_lossy()))
+            }
+            _ => None,
+        };
+        let owner_filter_legend = self.owner_filter.map(|owner_filter| {
+            let kind = match owner_filter.kind {
+                OwnerKind::User => "user",
+                OwnerKind::Group => "group",
+            };
+            format!("{}: {}", kind, owner_filter.name.to_string_lossy())
+        });
+        let search_filter_legend = self
+            .search_filter
+            .map(|search_filter| format!("filter: /{}", search_filter));
+        let legends: Vec<String> = vec![view_legend, owner_filter_legend, search_filter_legend]
+            .into_iter()
+            .flatten()
+            .collect();
+        let filter_legend = if legends.is_empty() {
+            None
+        } else {
+            Some(legends.join(", "))
+        };
+        let filter_len = match &filter_legend {
+            Some(filter_legend) => filter_legend.chars().count() as u16 + 2,
+            None => 0,
+        };
+        let show_filter_legend = filter_len > 0
+            && area.width > small_files_len + undo_len + marked_len + filter_len + 1 + 50;
+        let filter_len = if show_filter_legend { filter_len } else { 0 };
+        let max_status_len = area.width - small_files_len - undo_len - marked_len - filter_len - 1;
         let max_controls_len = area.width - 1;
         let status_line_y = area.y + area.height - 2;
         let controls_line_y = status_line_y + 1;
         if let Some(currently_selected) = self.currently_selected {
-            render_currently_selected(buf, currently_selected, max_status_len, status_line_y);
+            render_currently_selected(
+                buf,
+                currently_selected,
+                self.show_apparent_size,
+                max_status_len,
+                status_line_y,
+            );
         } else if let Some(last_read_path) = self.last_read_path {
             render_last_read_path(buf, last_read_path, max_status_len, status_line_y);
         }
@@ -173,6 +409,43 @@ impl<'a> Widget for BottomLine<'a> {
             );
         }
 
-        render_controls_legend(buf, self.hide_delete, max_controls_len, controls_line_y);
+        if show_undo_legend {
+            buf.set_string(
+                area.width - small_files_len - undo_len,
+                status_line_y,
+                undo_legend,
+                Style::default().add_modifier(Modifier::BOLD),
+            );
+        }
+
+        if let (true, Some(marked_legend)) = (show_marked_legend, marked_legend) {
+            buf.set_string(
+                area.width - small_files_len - undo_len - marked_len,
+                status_line_y,
+                marked_legend,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+
+        if let (true, Some(filter_legend)) = (show_filter_legend, filter_legend) {
+            buf.set_string(
+                area.width - small_files_len - undo_len - marked_len - filter_len,
+                status_line_y,
+                filter_legend,
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+
+        if let Some(search) = self.search {
+            render_search_prompt(buf, search, max_controls_len, controls_line_y);
+        } else if let Some(path_prompt) = self.path_prompt {
+            render_path_prompt(buf, path_prompt, max_controls_len, controls_line_y);
+        } else {
+            render_controls_legend(buf, self.hide_delete, max_controls_len, controls_line_y);
+        }
     }
 }
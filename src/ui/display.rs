@@ -7,7 +7,7 @@ use crate::state::files::FileTree;
 use crate::state::tiles::Board;
 use crate::state::UiEffects;
 use crate::ui::grid::RectangleGrid;
-use crate::ui::modals::{ConfirmBox, ErrorBox, MessageBox, WarningBox};
+use crate::ui::modals::{ConfirmBox, ErrorBox, HelpBox, MessageBox, SearchBox, WarningBox};
 use crate::ui::title::TitleLine;
 use crate::ui::{BottomLine, TermTooSmall};
 use crate::UiMode;
@@ -16,6 +16,7 @@ pub struct FolderInfo<'a> {
     pub path: &'a PathBuf,
     pub size: u128,
     pub num_descendants: u64,
+    pub growth: Option<i128>,
 }
 
 pub struct Display<B>
@@ -51,19 +52,23 @@ where
                 let current_path = file_tree.get_current_path();
                 let current_path_size = file_tree.get_current_folder_size();
                 let current_path_descendants = file_tree.get_current_folder().num_descendants;
+                let current_path_growth = file_tree.get_current_folder().growth;
                 let base_path_size = file_tree.get_total_size();
                 let base_path_descendants = file_tree.get_total_descendants();
                 let current_path_info = FolderInfo {
                     path: &current_path,
                     size: current_path_size,
                     num_descendants: current_path_descendants,
+                    growth: current_path_growth,
                 };
                 let path_in_filesystem = &file_tree.path_in_filesystem;
                 let base_path_info = FolderInfo {
                     path: &path_in_filesystem,
                     size: base_path_size,
                     num_descendants: base_path_descendants,
+                    growth: file_tree.get_base_folder().growth,
                 };
+                let hide_delete = file_tree.is_diff() || ui_effects.read_only;
                 let mut chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .margin(0)
@@ -93,6 +98,8 @@ where
                             .progress_indicator(ui_effects.loading_progress_indicator)
                             .path_error(ui_effects.current_path_is_red)
                             .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run)
                             .zoom_level(board.zoom_level)
                             .show_loading(),
                             chunks[0],
@@ -108,8 +115,9 @@ where
                         f.render_widget(
                             BottomLine::new()
                                 .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
                                 .last_read_path(ui_effects.last_read_path.as_ref())
-                                .hide_delete()
+                                .hide_delete(true)
                                 .hide_small_files_legend(
                                     board.unrenderable_tile_coordinates.is_none(),
                                 ),
@@ -117,6 +125,50 @@ where
                         );
                     }
                     UiMode::Normal => {
+                        let title_line = TitleLine::new(
+                            base_path_info,
+                            current_path_info,
+                            file_tree.space_freed,
+                        )
+                        .path_error(ui_effects.current_path_is_red)
+                        .flash_space(ui_effects.flash_space_freed)
+                        .zoom_level(board.zoom_level)
+                        .read_errors(file_tree.failed_to_read)
+                        .excluded(file_tree.excluded)
+                        .dry_run(ui_effects.dry_run);
+                        let title_line = if ui_effects.rescanning_folder {
+                            title_line
+                                .progress_indicator(ui_effects.loading_progress_indicator)
+                                .show_loading()
+                        } else {
+                            title_line
+                        };
+                        f.render_widget(title_line, chunks[0]);
+                        f.render_widget(
+                            RectangleGrid::new(
+                                &board.tiles,
+                                board.unrenderable_tile_coordinates,
+                                board.selected_index,
+                            ),
+                            chunks[1],
+                        );
+                        f.render_widget(
+                            BottomLine::new()
+                                .currently_selected(board.currently_selected())
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
+                                .search_filter(ui_effects.search_filter.as_deref())
+                                .view(&board.view)
+                                .owner_filter(board.owner_filter())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .hide_small_files_legend(
+                                    board.unrenderable_tile_coordinates.is_none(),
+                                ),
+                            chunks[2],
+                        );
+                    }
+                    UiMode::Search(search) => {
                         f.render_widget(
                             TitleLine::new(
                                 base_path_info,
@@ -126,7 +178,9 @@ where
                             .path_error(ui_effects.current_path_is_red)
                             .flash_space(ui_effects.flash_space_freed)
                             .zoom_level(board.zoom_level)
-                            .read_errors(file_tree.failed_to_read),
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run),
                             chunks[0],
                         );
                         f.render_widget(
@@ -140,6 +194,54 @@ where
                         f.render_widget(
                             BottomLine::new()
                                 .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
+                                .search(Some(search))
+                                .hide_small_files_legend(
+                                    board.unrenderable_tile_coordinates.is_none(),
+                                ),
+                            chunks[2],
+                        );
+                        if search.is_recursive {
+                            f.render_widget(SearchBox::new(search), chunks[1]);
+                        }
+                    }
+                    UiMode::PathPrompt(path_prompt) => {
+                        f.render_widget(
+                            TitleLine::new(
+                                base_path_info,
+                                current_path_info,
+                                file_tree.space_freed,
+                            )
+                            .path_error(ui_effects.current_path_is_red)
+                            .flash_space(ui_effects.flash_space_freed)
+                            .zoom_level(board.zoom_level)
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run),
+                            chunks[0],
+                        );
+                        f.render_widget(
+                            RectangleGrid::new(
+                                &board.tiles,
+                                board.unrenderable_tile_coordinates,
+                                board.selected_index,
+                            ),
+                            chunks[1],
+                        );
+                        f.render_widget(
+                            BottomLine::new()
+                                .currently_selected(board.currently_selected())
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
+                                .search_filter(ui_effects.search_filter.as_deref())
+                                .view(&board.view)
+                                .owner_filter(board.owner_filter())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .path_prompt(Some(path_prompt))
                                 .hide_small_files_legend(
                                     board.unrenderable_tile_coordinates.is_none(),
                                 ),
@@ -158,7 +260,50 @@ where
                             )
                             .path_error(ui_effects.current_path_is_red)
                             .zoom_level(board.zoom_level)
-                            .read_errors(file_tree.failed_to_read),
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run),
+                            chunks[0],
+                        );
+                        f.render_widget(
+                            RectangleGrid::new(
+                                &board.tiles,
+                                board.unrenderable_tile_coordinates,
+                                board.selected_index,
+                            ),
+                            chunks[1],
+                        );
+                        f.render_widget(
+                            BottomLine::new()
+                                .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
+                                .hide_small_files_legend(
+                                    board.unrenderable_tile_coordinates.is_none(),
+                                ),
+                            chunks[2],
+                        );
+                        f.render_widget(
+                            MessageBox::new(file_to_delete, ui_effects.deletion_in_progress)
+                                .dry_run(ui_effects.dry_run)
+                                .progress(ui_effects.deletion_progress),
+                            full_screen,
+                        );
+                    }
+                    UiMode::DeleteMarkedFiles(files_to_delete) => {
+                        f.render_widget(
+                            TitleLine::new(
+                                base_path_info,
+                                current_path_info,
+                                file_tree.space_freed,
+                            )
+                            .path_error(ui_effects.current_path_is_red)
+                            .zoom_level(board.zoom_level)
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run),
                             chunks[0],
                         );
                         f.render_widget(
@@ -172,13 +317,22 @@ where
                         f.render_widget(
                             BottomLine::new()
                                 .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
                                 .hide_small_files_legend(
                                     board.unrenderable_tile_coordinates.is_none(),
                                 ),
                             chunks[2],
                         );
                         f.render_widget(
-                            MessageBox::new(file_to_delete, ui_effects.deletion_in_progress),
+                            MessageBox::marked_files(
+                                files_to_delete,
+                                ui_effects.deletion_in_progress,
+                            )
+                            .dry_run(ui_effects.dry_run)
+                            .progress(ui_effects.deletion_progress),
                             full_screen,
                         );
                     }
@@ -192,7 +346,9 @@ where
                             .path_error(ui_effects.current_path_is_red)
                             .flash_space(ui_effects.flash_space_freed)
                             .zoom_level(board.zoom_level)
-                            .read_errors(file_tree.failed_to_read),
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run),
                             chunks[0],
                         );
                         f.render_widget(
@@ -206,6 +362,10 @@ where
                         f.render_widget(
                             BottomLine::new()
                                 .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
+                                .hide_delete(hide_delete)
+                                .undo_available(ui_effects.undo_available)
+                                .marked(ui_effects.marked)
                                 .hide_small_files_legend(
                                     board.unrenderable_tile_coordinates.is_none(),
                                 ),
@@ -213,6 +373,39 @@ where
                         );
                         f.render_widget(ErrorBox::new(message), full_screen);
                     }
+                    UiMode::Help => {
+                        f.render_widget(
+                            TitleLine::new(
+                                base_path_info,
+                                current_path_info,
+                                file_tree.space_freed,
+                            )
+                            .path_error(ui_effects.current_path_is_red)
+                            .flash_space(ui_effects.flash_space_freed)
+                            .zoom_level(board.zoom_level)
+                            .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded),
+                            chunks[0],
+                        );
+                        f.render_widget(
+                            RectangleGrid::new(
+                                &board.tiles,
+                                board.unrenderable_tile_coordinates,
+                                board.selected_index,
+                            ),
+                            chunks[1],
+                        );
+                        f.render_widget(
+                            BottomLine::new()
+                                .currently_selected(board.currently_selected())
+                                .hide_delete(hide_delete)
+                                .hide_small_files_legend(
+                                    board.unrenderable_tile_coordinates.is_none(),
+                                ),
+                            chunks[2],
+                        );
+                        f.render_widget(HelpBox::new().hide_delete(hide_delete), full_screen);
+                    }
                     UiMode::Exiting { app_loaded } => {
                         if *app_loaded {
                             // render normal ui mode
@@ -225,12 +418,18 @@ where
                                 .path_error(ui_effects.current_path_is_red)
                                 .flash_space(ui_effects.flash_space_freed)
                                 .zoom_level(board.zoom_level)
-                                .read_errors(file_tree.failed_to_read),
+                                .read_errors(file_tree.failed_to_read)
+                                .excluded(file_tree.excluded)
+                                .dry_run(ui_effects.dry_run),
                                 chunks[0],
                             );
                             f.render_widget(
                                 BottomLine::new()
                                     .currently_selected(board.currently_selected())
+                                    .show_apparent_size(file_tree.show_apparent_size)
+                                    .hide_delete(hide_delete)
+                                    .undo_available(ui_effects.undo_available)
+                                    .marked(ui_effects.marked)
                                     .hide_small_files_legend(
                                         board.unrenderable_tile_coordinates.is_none(),
                                     ),
@@ -248,14 +447,17 @@ where
                                 .path_error(ui_effects.current_path_is_red)
                                 .zoom_level(board.zoom_level)
                                 .read_errors(file_tree.failed_to_read)
+                                .excluded(file_tree.excluded)
+                                .dry_run(ui_effects.dry_run)
                                 .show_loading(),
                                 chunks[0],
                             );
                             f.render_widget(
                                 BottomLine::new()
                                     .currently_selected(board.currently_selected())
+                                    .show_apparent_size(file_tree.show_apparent_size)
                                     .last_read_path(ui_effects.last_read_path.as_ref())
-                                    .hide_delete()
+                                    .hide_delete(true)
                                     .hide_small_files_legend(
                                         board.unrenderable_tile_coordinates.is_none(),
                                     ),
@@ -283,6 +485,8 @@ where
                             .progress_indicator(ui_effects.loading_progress_indicator)
                             .path_error(ui_effects.current_path_is_red)
                             .read_errors(file_tree.failed_to_read)
+                            .excluded(file_tree.excluded)
+                            .dry_run(ui_effects.dry_run)
                             .show_loading(),
                             chunks[0],
                         );
@@ -297,8 +501,9 @@ where
                         f.render_widget(
                             BottomLine::new()
                                 .currently_selected(board.currently_selected())
+                                .show_apparent_size(file_tree.show_apparent_size)
                                 .last_read_path(ui_effects.last_read_path.as_ref())
-                                .hide_delete()
+                                .hide_delete(true)
                                 .hide_small_files_legend(
                                     board.unrenderable_tile_coordinates.is_none(),
                                 ),
@@ -16,6 +16,15 @@ impl fmt::Display for DisplaySize {
     }
 }
 
+pub struct DisplayGrowth(pub i128);
+
+impl fmt::Display for DisplayGrowth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "+" };
+        write!(f, "{}{}", sign, DisplaySize(self.0.abs() as f64))
+    }
+}
+
 pub struct DisplaySizeRounded(pub f64);
 
 impl fmt::Display for DisplaySizeRounded {
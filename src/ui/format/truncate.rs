@@ -39,7 +39,7 @@ pub fn truncate_middle(row: &str, max_length: u16) -> String {
 pub fn truncate_end(row: &str, max_len: u16) -> String {
     if row.chars().count() > max_len as usize {
         let mut truncated = String::from(row);
-        truncated.truncate(max_len as usize - 3);
+        truncated.truncate((max_len as usize).saturating_sub(3));
         format!("{}...", truncated)
     } else {
         row.to_string()
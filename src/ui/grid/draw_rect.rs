@@ -4,26 +4,45 @@ use ::tui::style::{Color, Modifier, Style};
 use ::unicode_width::UnicodeWidthStr;
 
 use crate::state::tiles::{FileType, Tile};
-use crate::ui::format::{truncate_middle, DisplaySize, DisplaySizeRounded};
+use crate::ui::format::{truncate_middle, DisplayGrowth, DisplaySize, DisplaySizeRounded};
 use crate::ui::grid::{boundaries, draw_next_symbol};
 
 fn tile_first_line(tile: &Tile) -> String {
     let max_text_length = if tile.width > 2 { tile.width - 2 } else { 0 };
     let name = &tile.name.to_string_lossy();
     let descendant_count = &tile.descendants;
+    let marker = if tile.is_marked { "* " } else { "" };
+    let lock = if tile.is_protected { "🔒" } else { "" };
     let filename_text = match tile.file_type {
-        FileType::File => format!("{}", name),
-        FileType::Folder => format!("{}/", name),
+        FileType::File => format!("{}{}{}", marker, lock, name),
+        FileType::Folder => format!("{}{}{}/", marker, lock, name),
     };
     match tile.file_type {
         FileType::File => truncate_middle(&filename_text, max_text_length),
+        FileType::Folder if tile.is_mount_point => {
+            // mount points are not scanned, so instead of their descendants
+            // we show why they are empty
+            let long_mount_point_indication = "(mount point, not scanned)";
+            let short_mount_point_indication = "(mount point)";
+            if filename_text.width() + long_mount_point_indication.len() <= max_text_length as usize
+            {
+                format!("{} {}", filename_text, long_mount_point_indication)
+            } else if filename_text.width() + short_mount_point_indication.len()
+                <= max_text_length as usize
+            {
+                format!("{} {}", filename_text, short_mount_point_indication)
+            } else {
+                truncate_middle(&filename_text, max_text_length)
+            }
+        }
         FileType::Folder => {
             let descendant_count = descendant_count.expect("folder should have descendants");
             let short_descendants_indication = format!("(+{})", descendant_count);
             let long_descendants_indication = format!("(+{} descendants)", descendant_count);
-            if filename_text.len() + long_descendants_indication.len() <= max_text_length as usize {
+            if filename_text.width() + long_descendants_indication.len() <= max_text_length as usize
+            {
                 format!("{} {}", filename_text, long_descendants_indication)
-            } else if filename_text.len() + short_descendants_indication.len()
+            } else if filename_text.width() + short_descendants_indication.len()
                 <= max_text_length as usize
             {
                 format!("{} {}", filename_text, short_descendants_indication)
@@ -37,11 +56,23 @@ fn tile_first_line(tile: &Tile) -> String {
 fn tile_second_line(tile: &Tile) -> String {
     let max_text_length = if tile.width > 2 { tile.width - 2 } else { 0 };
     let percentage = &tile.percentage;
-    let display_size = DisplaySize(tile.size as f64);
-    let display_size_rounded = DisplaySizeRounded(tile.size as f64);
-    let display_size = format!("{}", display_size);
-    let display_size_rounded = format!("{}", display_size_rounded);
-    if max_text_length >= display_size.len() as u16 + 7 {
+    let (display_size, display_size_rounded) = match tile.growth {
+        Some(growth) => {
+            let sign = if growth < 0 { "-" } else { "+" };
+            (
+                format!("{}", DisplayGrowth(growth)),
+                format!("{}{}", sign, DisplaySizeRounded(growth.abs() as f64)),
+            )
+        }
+        None => (
+            format!("{}", DisplaySize(tile.size as f64)),
+            format!("{}", DisplaySizeRounded(tile.size as f64)),
+        ),
+    };
+    if tile.is_hard_linked && max_text_length >= display_size.len() as u16 + 19 {
+        // 19 == "(100%, hard links)" + 1 space
+        format!("{} ({:.0}%, hard links)", display_size, percentage * 100.0)
+    } else if max_text_length >= display_size.len() as u16 + 7 {
         // 7 == "(100%)" + 1 space
         format!("{} ({:.0}%)", display_size, percentage * 100.0)
     } else if max_text_length > display_size.len() as u16 {
@@ -90,6 +121,30 @@ pub fn tile_style(tile: &Tile, selected: bool) -> (Option<Style>, Style, Style)
             Style::default(),
         ),
     };
+    let (first_line_style, second_line_style) = if tile.is_stale && !selected {
+        (
+            first_line_style.fg(Color::Cyan),
+            second_line_style.fg(Color::Cyan),
+        )
+    } else {
+        (first_line_style, second_line_style)
+    };
+    let first_line_style = if tile.is_marked && !selected {
+        first_line_style.fg(Color::Yellow)
+    } else {
+        first_line_style
+    };
+    let (first_line_style, second_line_style) = match (selected, tile.growth) {
+        (false, Some(growth)) if growth != 0 => {
+            // when comparing scans, growth is red and shrinkage is green
+            let growth_color = if growth > 0 { Color::Red } else { Color::Green };
+            (
+                first_line_style.fg(growth_color),
+                second_line_style.fg(growth_color),
+            )
+        }
+        _ => (first_line_style, second_line_style),
+    };
     (background_style, first_line_style, second_line_style)
 }
 
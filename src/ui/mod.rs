@@ -1,6 +1,6 @@
 mod bottom_line;
 mod display;
-mod format;
+pub mod format;
 mod grid;
 mod modals;
 mod term_too_small;
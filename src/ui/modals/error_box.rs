@@ -6,6 +6,8 @@ use ::tui::widgets::Widget;
 use crate::ui::format::truncate_end;
 use crate::ui::grid::draw_filled_rect;
 
+const MAX_ERROR_LINES: usize = 5;
+
 pub struct ErrorBox<'a> {
     error_message: &'a str,
 }
@@ -42,23 +44,39 @@ impl<'a> Widget for ErrorBox<'a> {
             .add_modifier(Modifier::BOLD);
         let text_max_length = message_rect.width - 4;
 
-        // here we truncate the end and not the middle because
-        // when dealing with error messages, the beginning tends
-        // to be the important part
-        let error_text = truncate_end(self.error_message, text_max_length);
-        let error_text_start_position =
-            ((message_rect.width - error_text.chars().count() as u16) as f64 / 2.0).ceil() as u16
-                + message_rect.x;
+        // messages with more than one line (eg. one error for each of several
+        // files) start higher up and only show as many lines as fit
+        let mut error_lines: Vec<String> = self.error_message.lines().map(String::from).collect();
+        if error_lines.len() > MAX_ERROR_LINES {
+            let hidden_lines = error_lines.len() - (MAX_ERROR_LINES - 1);
+            error_lines.truncate(MAX_ERROR_LINES - 1);
+            error_lines.push(format!("... and {} more", hidden_lines));
+        }
+        let first_line_y = if error_lines.len() > 1 {
+            message_rect.y + 1
+        } else {
+            message_rect.y + message_rect.height / 2 - 2
+        };
 
         let controls_text = ["(Press <ESC> to dismiss)", "(<ESC> to dismiss)"];
 
         draw_filled_rect(buf, fill_style, &message_rect);
-        buf.set_string(
-            error_text_start_position,
-            message_rect.y + message_rect.height / 2 - 2,
-            error_text,
-            fill_style,
-        );
+        for (index, error_line) in error_lines.iter().enumerate() {
+            // here we truncate the end and not the middle because
+            // when dealing with error messages, the beginning tends
+            // to be the important part
+            let error_text = truncate_end(error_line, text_max_length);
+            let error_text_start_position =
+                ((message_rect.width - error_text.chars().count() as u16) as f64 / 2.0).ceil()
+                    as u16
+                    + message_rect.x;
+            buf.set_string(
+                error_text_start_position,
+                first_line_y + index as u16,
+                error_text,
+                fill_style,
+            );
+        }
 
         for line in controls_text.iter() {
             if text_max_length >= line.chars().count() as u16 {
@@ -0,0 +1,117 @@
+use ::tui::buffer::Buffer;
+use ::tui::layout::Rect;
+use ::tui::style::{Color, Modifier, Style};
+use ::tui::widgets::Widget;
+
+use crate::ui::format::truncate_end;
+use crate::ui::grid::draw_filled_rect;
+
+// the controls legend at the bottom of the screen only has room for a few keys,
+// so all of them are listed here: (key, what it does, whether it deletes files)
+const KEYS: &[(&str, &str, bool)] = &[
+    ("<arrows>", "move around", false),
+    ("<ENTER>", "enter folder", false),
+    ("<ESC>", "parent folder", false),
+    ("<BACKSPACE>", "delete", true),
+    ("<u>", "undo the last deletion", true),
+    ("<SPACE>", "mark to delete together", true),
+    ("<r>", "rescan the current folder", false),
+    ("</>", "search this folder or everywhere", false),
+    ("<:/g>", "go to a path", false),
+    ("<t>", "group by file type", false),
+    ("<f>", "show the largest files", false),
+    ("<o>", "group by user, then by group", false),
+    ("<a>", "switch between apparent and disk size", false),
+    ("<+/-/0>", "zoom in/out/reset", false),
+    ("<?>", "show this help", false),
+    ("<q>", "quit", false),
+];
+
+pub struct HelpBox {
+    hide_delete: bool,
+}
+
+impl HelpBox {
+    pub fn new() -> Self {
+        Self { hide_delete: false }
+    }
+    pub fn hide_delete(mut self, should_hide_delete: bool) -> Self {
+        self.hide_delete = should_hide_delete;
+        self
+    }
+}
+
+impl Widget for HelpBox {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let keys: Vec<&(&str, &str, bool)> = KEYS
+            .iter()
+            .filter(|(_, _, deletes)| !(self.hide_delete && *deletes))
+            .collect();
+        let width = if area.width > 150 {
+            150
+        } else if area.width >= 50 {
+            area.width / 2
+        } else {
+            unreachable!("app should not be rendered if window is so small")
+        };
+        // 7 for the borders, the title, the controls and the empty lines around them
+        let height = std::cmp::min(keys.len() as u16 + 7, area.height - 1);
+
+        // position self in the middle of the rect
+        let x = ((area.x + area.width) / 2) - width / 2;
+        let y = ((area.y + area.height) / 2) - height / 2;
+
+        let help_rect = Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+        let fill_style = Style::default()
+            .bg(Color::Black)
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let text_max_length = help_rect.width - 4;
+
+        draw_filled_rect(buf, fill_style, &help_rect);
+
+        let title_text = "Keys";
+        let title_text_start_position =
+            ((help_rect.width - title_text.len() as u16) as f64 / 2.0).ceil() as u16 + help_rect.x;
+        buf.set_string(
+            title_text_start_position,
+            help_rect.y + 2,
+            title_text,
+            fill_style,
+        );
+
+        // in small windows we only list the keys that fit
+        let key_len = keys.iter().map(|(key, _, _)| key.len()).max().unwrap_or(0);
+        let keys_shown = (help_rect.height - 7) as usize;
+        for (index, (key, action, _)) in keys.iter().take(keys_shown).enumerate() {
+            let line = format!("{:width$}  {}", key, action, width = key_len);
+            buf.set_string(
+                help_rect.x + 2,
+                help_rect.y + 4 + index as u16,
+                truncate_end(&line, text_max_length),
+                fill_style,
+            );
+        }
+
+        let controls_text = ["(Press <ESC> to dismiss)", "(<ESC> to dismiss)"];
+        for line in controls_text.iter() {
+            if text_max_length >= line.chars().count() as u16 {
+                let start_position = ((help_rect.width - line.chars().count() as u16) as f64 / 2.0)
+                    .ceil() as u16
+                    + help_rect.x;
+                buf.set_string(
+                    start_position,
+                    help_rect.y + help_rect.height - 2,
+                    line,
+                    fill_style,
+                );
+                break;
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
+use ::std::slice;
+
 use ::tui::buffer::Buffer;
 use ::tui::layout::Rect;
 use ::tui::style::{Color, Modifier, Style};
 use ::tui::widgets::Widget;
 
+use crate::deleter::DeletionProgress;
 use crate::state::tiles::FileType;
 use crate::state::FileToDelete;
-use crate::ui::format::truncate_middle;
+use crate::ui::format::{truncate_end, truncate_middle, DisplaySize};
 use crate::ui::grid::draw_filled_rect;
 
 fn truncated_file_name_line(file_to_delete: &FileToDelete, max_len: u16) -> String {
@@ -28,15 +31,20 @@ fn truncated_file_name_line(file_to_delete: &FileToDelete, max_len: u16) -> Stri
     }
 }
 
-fn render_deletion_prompt(buf: &mut Buffer, message_rect: &Rect, file_to_delete: &FileToDelete) {
+fn render_deletion_prompt(
+    buf: &mut Buffer,
+    message_rect: &Rect,
+    file_to_delete: &FileToDelete,
+    dry_run: bool,
+) {
     let max_text_len = message_rect.width - 4;
     let file_name_line = truncated_file_name_line(file_to_delete, max_text_len);
     let text_style = Style::default()
         .bg(Color::Black)
         .fg(Color::Red)
         .add_modifier(Modifier::BOLD);
-    let question_line = match file_to_delete.file_type {
-        FileType::File => {
+    let question_line = match (file_to_delete.file_type, file_to_delete.move_to_trash) {
+        (FileType::File, false) => {
             if max_text_len >= 17 {
                 "Delete this file?".to_string()
             } else if max_text_len >= 3 {
@@ -45,12 +53,32 @@ fn render_deletion_prompt(buf: &mut Buffer, message_rect: &Rect, file_to_delete:
                 unreachable!("should not render if terminal is so small");
             }
         }
-        FileType::Folder => {
+        (FileType::File, true) => {
+            if max_text_len >= 24 {
+                "Move this file to trash?".to_string()
+            } else if max_text_len >= 14 {
+                "Move to trash?".to_string()
+            } else if max_text_len >= 6 {
+                "Trash?".to_string()
+            } else {
+                unreachable!("should not render if terminal is so small");
+            }
+        }
+        (FileType::Folder, move_to_trash) => {
             let children = file_to_delete
                 .num_descendants
                 .expect("folder should have descendants");
-            let full_line = format!("Delete folder with {} children?", children);
-            let short_line = "Delete folder?".to_string();
+            let (full_line, short_line) = if move_to_trash {
+                (
+                    format!("Move folder with {} children to trash?", children),
+                    "Trash folder?".to_string(),
+                )
+            } else {
+                (
+                    format!("Delete folder with {} children?", children),
+                    "Delete folder?".to_string(),
+                )
+            };
             if max_text_len >= full_line.len() as u16 {
                 full_line
             } else if max_text_len >= short_line.len() as u16 {
@@ -60,7 +88,18 @@ fn render_deletion_prompt(buf: &mut Buffer, message_rect: &Rect, file_to_delete:
             }
         }
     };
-    let y_n_line = "(y/n)";
+    // <t> switches between moving to the trash and deleting permanently
+    let full_y_n_line = if file_to_delete.move_to_trash {
+        "(y/n, <t> - delete instead)"
+    } else {
+        "(y/n, <t> - move to trash)"
+    };
+    // in a dry run nothing is deleted, so there is no point in choosing the trash
+    let y_n_line = if !dry_run && max_text_len >= full_y_n_line.len() as u16 {
+        full_y_n_line
+    } else {
+        "(y/n)"
+    };
     let question_line_start_position =
         ((message_rect.width - question_line.len() as u16) as f64 / 2.0).ceil() as u16
             + message_rect.x;
@@ -89,50 +128,176 @@ fn render_deletion_prompt(buf: &mut Buffer, message_rect: &Rect, file_to_delete:
     );
 }
 
+fn render_marked_files_deletion_prompt(
+    buf: &mut Buffer,
+    message_rect: &Rect,
+    files_to_delete: &[FileToDelete],
+    dry_run: bool,
+) {
+    let max_text_len = message_rect.width - 4;
+    let text_style = Style::default()
+        .bg(Color::Black)
+        .fg(Color::Red)
+        .add_modifier(Modifier::BOLD);
+    let move_to_trash = files_to_delete
+        .iter()
+        .all(|file_to_delete| file_to_delete.move_to_trash);
+    let (full_line, short_line) = if move_to_trash {
+        (
+            format!("Move {} marked items to trash?", files_to_delete.len()),
+            format!("Trash {} items?", files_to_delete.len()),
+        )
+    } else {
+        (
+            format!("Delete {} marked items?", files_to_delete.len()),
+            format!("Delete {} items?", files_to_delete.len()),
+        )
+    };
+    let question_line = if max_text_len >= full_line.len() as u16 {
+        full_line
+    } else if max_text_len >= short_line.len() as u16 {
+        short_line
+    } else {
+        unreachable!("should not render if terminal is so small");
+    };
+    let total_size: u128 = files_to_delete
+        .iter()
+        .map(|file_to_delete| file_to_delete.size)
+        .sum();
+    let size_line = format!("{} in total", DisplaySize(total_size as f64));
+    let full_y_n_line = if move_to_trash {
+        "(y/n, <t> - delete instead)"
+    } else {
+        "(y/n, <t> - move to trash)"
+    };
+    let y_n_line = if !dry_run && max_text_len >= full_y_n_line.len() as u16 {
+        full_y_n_line
+    } else {
+        "(y/n)"
+    };
+    let lines = [
+        (
+            question_line.as_str(),
+            message_rect.y + message_rect.height / 2 - 3,
+        ),
+        (size_line.as_str(), message_rect.y + message_rect.height / 2),
+        (y_n_line, message_rect.y + message_rect.height / 2 + 3),
+    ];
+    for (line, y) in lines.iter() {
+        let start_position = ((message_rect.width.saturating_sub(line.len() as u16)) as f64 / 2.0)
+            .ceil() as u16
+            + message_rect.x;
+        buf.set_string(start_position, *y, line, text_style);
+    }
+}
+
+fn progress_bar(progress: &DeletionProgress, files_to_remove: u64, width: u16) -> String {
+    // the count can run past the scanned one if files were added in the meantime
+    let done = (progress.removed.min(files_to_remove) * width as u64)
+        .checked_div(files_to_remove)
+        .unwrap_or(0) as usize;
+    let mut bar = "█".repeat(done);
+    bar.push_str(&"░".repeat(width as usize - done));
+    bar
+}
+
 fn render_deletion_in_progress(
     buf: &mut Buffer,
     message_rect: &Rect,
-    file_to_delete: &FileToDelete,
+    files_to_delete: &[FileToDelete],
+    progress: Option<(DeletionProgress, u64)>,
 ) {
     let max_text_len = message_rect.width - 4;
-    let file_name_line = truncated_file_name_line(file_to_delete, max_text_len);
-    let deleting_line = "Deleting";
+    let move_to_trash = files_to_delete
+        .iter()
+        .all(|file_to_delete| file_to_delete.move_to_trash);
+    let file_name_line = match files_to_delete {
+        [file_to_delete] => truncated_file_name_line(file_to_delete, max_text_len),
+        _ => format!("{} items", files_to_delete.len()),
+    };
+    let deleting_line = if move_to_trash {
+        "Moving to trash"
+    } else {
+        "Deleting"
+    };
     let text_style = Style::default()
         .bg(Color::Black)
         .fg(Color::Red)
         .add_modifier(Modifier::BOLD);
-    let deleting_line_start_position =
-        ((message_rect.width - deleting_line.len() as u16) as f64 / 2.0).ceil() as u16
+    let middle = message_rect.y + message_rect.height / 2;
+    let mut lines = vec![];
+    match progress {
+        Some((progress, files_to_remove)) => {
+            let full_progress_line = format!(
+                "{} files, {} freed (<ESC> to cancel)",
+                progress.removed,
+                DisplaySize(progress.space_freed as f64)
+            );
+            let short_progress_line = format!(
+                "{} freed (<ESC> to cancel)",
+                DisplaySize(progress.space_freed as f64)
+            );
+            let progress_line = if max_text_len >= full_progress_line.len() as u16 {
+                full_progress_line
+            } else {
+                truncate_end(&short_progress_line, max_text_len)
+            };
+            lines.push((deleting_line.to_string(), middle - 3));
+            lines.push((file_name_line, middle - 1));
+            lines.push((
+                progress_bar(&progress, files_to_remove, max_text_len),
+                middle + 1,
+            ));
+            lines.push((progress_line, middle + 3));
+        }
+        None => {
+            lines.push((deleting_line.to_string(), middle - 1));
+            lines.push((file_name_line, middle + 1));
+        }
+    }
+    for (line, y) in lines {
+        // the progress bar's characters are more than a byte long
+        let line_len = line.chars().count() as u16;
+        let start_position = ((message_rect.width.saturating_sub(line_len)) as f64 / 2.0).ceil()
+            as u16
             + message_rect.x;
-    let file_line_start_position = ((message_rect.width - file_name_line.len() as u16) as f64 / 2.0)
-        .ceil() as u16
-        + message_rect.x;
-    buf.set_string(
-        deleting_line_start_position,
-        message_rect.y + message_rect.height / 2 - 1,
-        deleting_line,
-        text_style,
-    );
-    buf.set_string(
-        file_line_start_position,
-        message_rect.y + message_rect.height / 2 + 1,
-        file_name_line,
-        text_style,
-    );
+        buf.set_string(start_position, y, line, text_style);
+    }
 }
 
 pub struct MessageBox<'a> {
-    file_to_delete: &'a FileToDelete,
+    files_to_delete: &'a [FileToDelete],
     deletion_in_progress: bool,
+    dry_run: bool,
+    // files removed so far out of how many, when deleting in the background
+    progress: Option<(DeletionProgress, u64)>,
 }
 
 impl<'a> MessageBox<'a> {
     pub fn new(file_to_delete: &'a FileToDelete, deletion_in_progress: bool) -> Self {
         Self {
-            file_to_delete,
+            files_to_delete: slice::from_ref(file_to_delete),
             deletion_in_progress,
+            dry_run: false,
+            progress: None,
         }
     }
+    pub fn marked_files(files_to_delete: &'a [FileToDelete], deletion_in_progress: bool) -> Self {
+        Self {
+            files_to_delete,
+            deletion_in_progress,
+            dry_run: false,
+            progress: None,
+        }
+    }
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+    pub fn progress(mut self, progress: Option<(DeletionProgress, u64)>) -> Self {
+        self.progress = progress;
+        self
+    }
 }
 
 impl<'a> Widget for MessageBox<'a> {
@@ -162,9 +327,16 @@ impl<'a> Widget for MessageBox<'a> {
 
         draw_filled_rect(buf, fill_style, &message_rect);
         if self.deletion_in_progress {
-            render_deletion_in_progress(buf, &message_rect, &self.file_to_delete);
+            render_deletion_in_progress(buf, &message_rect, self.files_to_delete, self.progress);
+        } else if let [file_to_delete] = self.files_to_delete {
+            render_deletion_prompt(buf, &message_rect, file_to_delete, self.dry_run);
         } else {
-            render_deletion_prompt(buf, &message_rect, &self.file_to_delete);
+            render_marked_files_deletion_prompt(
+                buf,
+                &message_rect,
+                self.files_to_delete,
+                self.dry_run,
+            );
         }
     }
 }
@@ -1,9 +1,13 @@
 mod confirm_box;
 mod error_box;
+mod help_box;
 mod message_box;
+mod search_box;
 mod warning_box;
 
 pub use confirm_box::*;
 pub use error_box::*;
+pub use help_box::*;
 pub use message_box::*;
+pub use search_box::*;
 pub use warning_box::*;
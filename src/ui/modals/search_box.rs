@@ -0,0 +1,104 @@
+use ::std::path::PathBuf;
+
+use ::tui::buffer::Buffer;
+use ::tui::layout::Rect;
+use ::tui::style::{Color, Modifier, Style};
+use ::tui::widgets::Widget;
+
+use crate::state::search::Search;
+use crate::state::tiles::FileType;
+use crate::ui::format::{truncate_end, truncate_middle, DisplaySize};
+use crate::ui::grid::draw_filled_rect;
+
+const MAX_HEIGHT: u16 = 24;
+
+// the matches of a search everywhere in the file tree
+pub struct SearchBox<'a> {
+    search: &'a Search,
+}
+
+impl<'a> SearchBox<'a> {
+    pub fn new(search: &'a Search) -> Self {
+        Self { search }
+    }
+}
+
+impl<'a> Widget for SearchBox<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // this is drawn over the board, which is a bit narrower than the window
+        let width = if area.width > 150 {
+            150
+        } else {
+            area.width / 2
+        };
+        let height = (area.height - 2).min(MAX_HEIGHT);
+
+        // position self in the middle of the rect
+        let x = area.x + (area.width - width) / 2;
+        let y = area.y + (area.height - height) / 2;
+
+        let search_rect = Rect {
+            x,
+            y,
+            width,
+            height,
+        };
+        let fill_style = Style::default().bg(Color::Black).fg(Color::White);
+        let text_max_length = search_rect.width - 4;
+
+        let title = if self.search.is_invalid() {
+            String::from("Invalid regex")
+        } else if self.search.query.is_empty() {
+            String::from("Type to search everywhere")
+        } else if self.search.results.is_empty() {
+            String::from("No matches")
+        } else {
+            format!("{} matches, largest first", self.search.results.len())
+        };
+        let title = truncate_end(&title, text_max_length);
+        draw_filled_rect(buf, fill_style, &search_rect);
+        let title_start_position = ((search_rect
+            .width
+            .saturating_sub(title.chars().count() as u16))
+            as f64
+            / 2.0)
+            .ceil() as u16
+            + search_rect.x;
+        buf.set_string(
+            title_start_position,
+            search_rect.y + 1,
+            title,
+            fill_style.add_modifier(Modifier::BOLD),
+        );
+
+        // we scroll so that the selected match is always shown
+        let lines = search_rect.height.saturating_sub(4) as usize;
+        let first_shown = (self.search.selected_index + 1).saturating_sub(lines);
+        for (index, result) in self
+            .search
+            .results
+            .iter()
+            .enumerate()
+            .skip(first_shown)
+            .take(lines)
+        {
+            let path: PathBuf = result.path_to_file.iter().collect();
+            // DisplaySize ignores the width, so it is padded as a string
+            let size = DisplaySize(result.size as f64).to_string();
+            let line = format!("{:>7}  {}", size, path.to_string_lossy());
+            let mut style = match result.file_type {
+                FileType::Folder => fill_style.fg(Color::Blue).add_modifier(Modifier::BOLD),
+                FileType::File => fill_style,
+            };
+            if index == self.search.selected_index {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            buf.set_string(
+                search_rect.x + 2,
+                search_rect.y + 3 + (index - first_shown) as u16,
+                truncate_middle(&line, text_max_length),
+                style,
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ use ::tui::layout::Rect;
 use ::tui::style::{Color, Modifier, Style};
 use ::tui::widgets::Widget;
 
-use crate::ui::format::DisplaySize;
+use crate::ui::format::{DisplayGrowth, DisplaySize};
 use crate::ui::title::{CellSizeOpt, TitleTelescope};
 use crate::ui::FolderInfo;
 
@@ -20,9 +20,11 @@ pub struct TitleLine<'a> {
     show_loading: bool,
     progress_indicator: u64,
     read_errors: Option<u64>,
+    excluded: Option<u64>,
     flash_space: bool,
     path_error: bool,
     zoom_level: Option<usize>,
+    dry_run: bool,
 }
 
 impl<'a> TitleLine<'a> {
@@ -37,10 +39,12 @@ impl<'a> TitleLine<'a> {
             space_freed,
             progress_indicator: 0,
             read_errors: None,
+            excluded: None,
             show_loading: false,
             flash_space: false,
             path_error: false,
             zoom_level: None,
+            dry_run: false,
         }
     }
     pub fn show_loading(mut self) -> Self {
@@ -65,6 +69,16 @@ impl<'a> TitleLine<'a> {
         }
         self
     }
+    pub fn excluded(mut self, excluded: u64) -> Self {
+        if excluded > 0 {
+            self.excluded = Some(excluded);
+        }
+        self
+    }
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
     pub fn zoom_level(mut self, zoom_level: usize) -> Self {
         if zoom_level > 0 {
             self.zoom_level = Some(zoom_level);
@@ -106,7 +120,10 @@ impl<'a> Widget for TitleLine<'a> {
         let separator = str::replace(&separator, "\\", "/");
         let total_size = DisplaySize(self.base_path_info.size as f64);
         let total_descendants = &self.base_path_info.num_descendants;
-        let current_folder_size = DisplaySize(self.current_path_info.size as f64);
+        let current_folder_size = match self.current_path_info.growth {
+            Some(growth) => format!("{}", DisplayGrowth(growth)),
+            None => format!("{}", DisplaySize(self.current_path_info.size as f64)),
+        };
         let current_folder_descendants = self.current_path_info.num_descendants;
         let space_freed = DisplaySize(self.space_freed as f64);
 
@@ -115,6 +132,15 @@ impl<'a> Widget for TitleLine<'a> {
             default_style = default_style.add_modifier(Modifier::BOLD);
         };
         let mut title_telescope = TitleTelescope::new(default_style);
+        if self.dry_run {
+            title_telescope.append_to_left_side(vec![CellSizeOpt::new(" DRY RUN ".to_string())
+                .style(
+                    Style::default()
+                        .bg(Color::Red)
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                )]);
+        }
         if self.show_loading {
             title_telescope.append_to_left_side(vec![
                 CellSizeOpt::new(format!(
@@ -124,6 +150,17 @@ impl<'a> Widget for TitleLine<'a> {
                 CellSizeOpt::new(format!("Scanning: {}", total_size)),
                 CellSizeOpt::new(format!("{}", total_size)),
             ]);
+        } else if let Some(total_growth) = self.base_path_info.growth {
+            let total_growth = DisplayGrowth(total_growth);
+            title_telescope.append_to_left_side(vec![
+                CellSizeOpt::new(format!(
+                    "Total growth: {} ({} changes)",
+                    total_growth, total_descendants
+                )),
+                CellSizeOpt::new(format!("Total growth: {}", total_growth)),
+                CellSizeOpt::new(format!("Growth: {}", total_growth)),
+                CellSizeOpt::new(format!("{}", total_growth)),
+            ]);
         } else {
             title_telescope.append_to_left_side(vec![
                 CellSizeOpt::new(format!(
@@ -144,6 +181,13 @@ impl<'a> Widget for TitleLine<'a> {
                 CellSizeOpt::new(" (errors)".to_string()).style(default_style.fg(Color::Red)),
             ]);
         }
+        if let Some(excluded) = self.excluded {
+            title_telescope.append_to_left_side(vec![
+                CellSizeOpt::new(format!(" (excluded {} files)", excluded)),
+                CellSizeOpt::new(format!(" ({} excluded)", excluded)),
+                CellSizeOpt::new(" (excluded)".to_string()),
+            ]);
+        }
         if is_user_admin() {
             title_telescope.append_to_left_side(vec![
                 CellSizeOpt::new(format!(" (CAUTION: running as root)"))
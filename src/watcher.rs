@@ -0,0 +1,297 @@
+use ::std::fs;
+use ::std::path::{Path, PathBuf};
+use ::std::sync::atomic::{AtomicBool, Ordering};
+use ::std::sync::mpsc::SyncSender;
+use ::std::sync::Arc;
+use ::std::thread::park_timeout;
+use ::std::time;
+
+use crate::messages::Instruction;
+use crate::scanner::{scan_state, walk_subfolder, ScanFilter, ScanState};
+
+#[cfg(target_os = "linux")]
+pub use self::inotify::Watcher;
+
+// a change to a single entry in one of the watched folders
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Updated(PathBuf),
+    Removed(PathBuf),
+    // a folder that was created or moved in, whose contents we need to read
+    FolderAdded(PathBuf),
+    // there were too many changes at once and some of them were lost
+    Overflowed,
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use ::std::collections::HashMap;
+    use ::std::os::unix::io::AsRawFd;
+    use ::std::path::Path;
+    use ::std::path::PathBuf;
+    use ::std::sync::{Arc, Mutex};
+
+    use ::nix::errno::Errno;
+    use ::nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, InotifyEvent, WatchDescriptor};
+    use ::nix::unistd::close;
+
+    use super::Change;
+
+    // closes the inotify fd once the last watcher sharing it is dropped
+    struct InotifyFd(Inotify);
+
+    impl Drop for InotifyFd {
+        fn drop(&mut self) {
+            let _ = close(self.0.as_raw_fd());
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct Watcher {
+        inotify: Arc<InotifyFd>,
+        watched_folders: Arc<Mutex<HashMap<WatchDescriptor, PathBuf>>>,
+    }
+
+    impl Watcher {
+        pub fn new() -> Option<Self> {
+            let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC).ok()?;
+            Some(Watcher {
+                inotify: Arc::new(InotifyFd(inotify)),
+                watched_folders: Arc::new(Mutex::new(HashMap::new())),
+            })
+        }
+        pub fn watch_folder(&self, path: &Path) {
+            let flags = AddWatchFlags::IN_CREATE
+                | AddWatchFlags::IN_DELETE
+                | AddWatchFlags::IN_MODIFY
+                | AddWatchFlags::IN_MOVED_FROM
+                | AddWatchFlags::IN_MOVED_TO
+                | AddWatchFlags::IN_ONLYDIR
+                | AddWatchFlags::IN_DONT_FOLLOW;
+            // this fails once we run out of watches (see fs.inotify.max_user_watches),
+            // in which case changes in the rest of the folders will not be shown
+            if let Ok(watch_descriptor) = self.inotify.0.add_watch(path, flags) {
+                self.watched_folders
+                    .lock()
+                    .expect("could not lock watched folders")
+                    .insert(watch_descriptor, path.to_path_buf());
+            }
+        }
+        fn event_to_change(&self, event: InotifyEvent) -> Option<Change> {
+            let mut watched_folders = self
+                .watched_folders
+                .lock()
+                .expect("could not lock watched folders");
+            if event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW) {
+                return Some(Change::Overflowed);
+            }
+            if event.mask.contains(AddWatchFlags::IN_IGNORED) {
+                // the watched folder itself was removed, which we
+                // hear about from the watch on its parent
+                watched_folders.remove(&event.wd);
+                return None;
+            }
+            let path = watched_folders.get(&event.wd)?.join(event.name?);
+            let is_folder = event.mask.contains(AddWatchFlags::IN_ISDIR);
+            let was_added = event
+                .mask
+                .intersects(AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO);
+            if event
+                .mask
+                .intersects(AddWatchFlags::IN_DELETE | AddWatchFlags::IN_MOVED_FROM)
+            {
+                Some(Change::Removed(path))
+            } else if is_folder && was_added {
+                Some(Change::FolderAdded(path))
+            } else if is_folder {
+                None
+            } else {
+                Some(Change::Updated(path))
+            }
+        }
+        pub fn read_changes(&self) -> Option<Vec<Change>> {
+            // returns None if we can no longer read changes
+            match self.inotify.0.read_events() {
+                Ok(events) => {
+                    let mut changes: Vec<Change> = vec![];
+                    for event in events {
+                        if let Some(change) = self.event_to_change(event) {
+                            // a file that is being written to sends lots of
+                            // events, we only need to look at it once
+                            if changes.last() != Some(&change) {
+                                changes.push(change);
+                            }
+                        }
+                    }
+                    Some(changes)
+                }
+                Err(::nix::Error::Sys(Errno::EAGAIN)) => Some(vec![]),
+                Err(_) => None,
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+#[derive(Clone)]
+pub struct Watcher;
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+    pub fn new() -> Option<Self> {
+        // we only know how to watch for changes with inotify
+        None
+    }
+    pub fn watch_folder(&self, _path: &Path) {}
+    pub fn read_changes(&self) -> Option<Vec<Change>> {
+        None
+    }
+}
+
+fn send_change(
+    change: Change,
+    watcher: &Watcher,
+    base_path: &Path,
+    scan_filter: &ScanFilter,
+    instruction_sender: &SyncSender<Instruction>,
+) -> Result<(), ()> {
+    match change {
+        Change::Removed(path) => instruction_sender
+            .send(Instruction::RemoveEntryFromBaseFolder(path))
+            .map_err(|_| ()),
+        Change::Updated(path) => match fs::symlink_metadata(&path) {
+            // it no longer falls in the --older-than / --newer-than window
+            Ok(metadata) if scan_filter.is_excluded_by_age(&metadata) => instruction_sender
+                .send(Instruction::RemoveEntryFromBaseFolder(path))
+                .map_err(|_| ()),
+            Ok(metadata) => instruction_sender
+                .send(Instruction::UpdateEntryInBaseFolder((metadata, path)))
+                .map_err(|_| ()),
+            // it was removed right after it changed, we'll hear about that too
+            Err(_) => Ok(()),
+        },
+        Change::Overflowed => instruction_sender
+            .send(Instruction::RescanBaseFolder)
+            .map_err(|_| ()),
+        Change::FolderAdded(path) => {
+            for entry in walk_subfolder(&path, base_path, scan_filter) {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => continue,
+                };
+                let instruction = match scan_state(&entry) {
+                    ScanState::Excluded => {
+                        Instruction::IncrementExcluded(entry.parent_path.to_path_buf())
+                    }
+                    ScanState::MountPoint => Instruction::AddMountPointToBaseFolder(entry.path()),
+                    ScanState::Scanned => match entry.metadata() {
+                        Ok(metadata) => {
+                            if metadata.is_dir() {
+                                watcher.watch_folder(&entry.path());
+                            }
+                            Instruction::UpdateEntryInBaseFolder((metadata, entry.path()))
+                        }
+                        Err(_) => continue,
+                    },
+                };
+                instruction_sender.send(instruction).map_err(|_| ())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn watch_folders(
+    watcher: Watcher,
+    base_path: PathBuf,
+    scan_filter: ScanFilter,
+    instruction_sender: SyncSender<Instruction>,
+    running: Arc<AtomicBool>,
+    loaded: Arc<AtomicBool>,
+) {
+    // changes that happen while scanning are kept until the scan is done, since the
+    // entries they touch might not have been added yet. we still read them as they
+    // come so that they do not overflow the kernel's (much smaller) queue
+    let mut changes_while_scanning: Vec<Change> = vec![];
+    'watching: while running.load(Ordering::Acquire) {
+        let mut changes = match watcher.read_changes() {
+            Some(changes) => changes,
+            None => break 'watching,
+        };
+        if !loaded.load(Ordering::Acquire) {
+            changes_while_scanning.append(&mut changes);
+            park_timeout(time::Duration::from_millis(100));
+            continue;
+        }
+        if !changes_while_scanning.is_empty() {
+            changes_while_scanning.append(&mut changes);
+            changes = changes_while_scanning.split_off(0);
+        }
+        if changes.is_empty() {
+            park_timeout(time::Duration::from_millis(100));
+            continue;
+        }
+        if changes.contains(&Change::Overflowed) {
+            // we read everything again anyway
+            changes = vec![Change::Overflowed];
+        }
+        for change in changes {
+            if let Change::Updated(path) | Change::FolderAdded(path) = &change {
+                if scan_filter.is_excluded_by_glob(path, &base_path) {
+                    continue;
+                }
+            }
+            // if we fail to send an instruction, the app has ended
+            if send_change(
+                change,
+                &watcher,
+                &base_path,
+                &scan_filter,
+                &instruction_sender,
+            )
+            .is_err()
+            {
+                break 'watching;
+            }
+        }
+        if instruction_sender
+            .send(Instruction::RenderAndUpdateBoard)
+            .is_err()
+        {
+            break 'watching;
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use ::std::fs::{create_dir_all, remove_dir_all, write};
+
+    use crate::scanner::{scan_into, scan_subfolder};
+
+    #[test]
+    fn watch_rescanned_folders() {
+        let path = PathBuf::from("/tmp/diskonaut_tests/watcher_rescan");
+        remove_dir_all(&path).ok();
+        create_dir_all(&path).expect("failed to create folder");
+        let watcher = Watcher::new().expect("failed to start watcher");
+        watcher.watch_folder(&path);
+
+        // a folder that appears after the first scan, and is found by a rescan
+        create_dir_all(path.join("subfolder")).expect("failed to create folder");
+        let scan_filter = ScanFilter::default();
+        let file_tree = scan_subfolder(path.clone(), &path, true, false, &scan_filter);
+        scan_into(
+            file_tree.subfolder_to_rescan(path.clone()),
+            &path,
+            &scan_filter,
+            Some(&watcher),
+        );
+        write(path.join("subfolder/file"), "x").expect("failed to create file");
+        let changes = watcher.read_changes().expect("failed to read changes");
+        remove_dir_all(&path).ok();
+
+        assert!(changes.contains(&Change::Updated(path.join("subfolder/file"))));
+    }
+}